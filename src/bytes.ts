@@ -0,0 +1,97 @@
+import { Bytes, Type } from "./rust-type";
+import { Decoder } from "./decode";
+import { Encoder } from "./encode";
+
+/**
+ * Hex and base64 text for byte fields, `HexBytes` and `Base64Bytes` decode a `Vec<u8>`
+ * directly as text, the layout is the same as `Bytes`.
+ */
+
+const BASE64_ALPHABET =
+  "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+export function bytesToHex(bytes: Uint8Array): string {
+  let hex = "";
+  for (const byte of bytes) {
+    hex += byte.toString(16).padStart(2, "0");
+  }
+  return hex;
+}
+
+export function hexToBytes(hex: string): Uint8Array {
+  if (!/^([0-9a-fA-F]{2})*$/.test(hex)) {
+    throw new Error(`invalid hex \`${hex}\``);
+  }
+  const bytes = new Uint8Array(hex.length / 2);
+  for (let idx = 0; idx < bytes.length; idx += 1) {
+    bytes[idx] = parseInt(hex.slice(idx * 2, idx * 2 + 2), 16);
+  }
+  return bytes;
+}
+
+/**
+ * standard base64 with padding
+ */
+export function bytesToBase64(bytes: Uint8Array): string {
+  let base64 = "";
+  for (let idx = 0; idx < bytes.length; idx += 3) {
+    const count = Math.min(3, bytes.length - idx);
+    const group =
+      (bytes[idx] << 16) |
+      ((count > 1 ? bytes[idx + 1] : 0) << 8) |
+      (count > 2 ? bytes[idx + 2] : 0);
+    for (let char = 0; char < 4; char += 1) {
+      base64 +=
+        char <= count
+          ? BASE64_ALPHABET[(group >> (18 - char * 6)) & 0x3f]
+          : "=";
+    }
+  }
+  return base64;
+}
+
+export function base64ToBytes(base64: string): Uint8Array {
+  if (!/^[A-Za-z0-9+/]*={0,2}$/.test(base64) || base64.length % 4 !== 0) {
+    throw new Error(`invalid base64 \`${base64}\``);
+  }
+  const padding = base64.endsWith("==") ? 2 : base64.endsWith("=") ? 1 : 0;
+  const bytes = new Uint8Array((base64.length / 4) * 3 - padding);
+  for (let idx = 0; idx < base64.length; idx += 4) {
+    let group = 0;
+    for (let char = 0; char < 4; char += 1) {
+      const value = BASE64_ALPHABET.indexOf(base64[idx + char]);
+      group = (group << 6) | Math.max(value, 0);
+    }
+    const offset = (idx / 4) * 3;
+    for (let byte = 0; byte < 3 && offset + byte < bytes.length; byte += 1) {
+      bytes[offset + byte] = (group >> (16 - byte * 8)) & 0xff;
+    }
+  }
+  return bytes;
+}
+
+/**
+ * `Vec<u8>` decoded as a lowercase hex string
+ */
+export const HexBytes: Type<string> = {
+  decode: (decoder: Decoder): string => {
+    return bytesToHex(Bytes.decode(decoder));
+  },
+  encode: (data: string, encoder: Encoder): void => {
+    Bytes.encode(hexToBytes(data), encoder);
+  },
+  skip: Bytes.skip,
+};
+
+/**
+ * `Vec<u8>` decoded as a base64 string
+ */
+export const Base64Bytes: Type<string> = {
+  decode: (decoder: Decoder): string => {
+    return bytesToBase64(Bytes.decode(decoder));
+  },
+  encode: (data: string, encoder: Encoder): void => {
+    Bytes.encode(base64ToBytes(data), encoder);
+  },
+  skip: Bytes.skip,
+};
@@ -0,0 +1,47 @@
+import { Str, Type } from "./rust-type";
+import { Decoder } from "./decode";
+import { Encoder } from "./encode";
+
+/**
+ * Types of the `chrono` crate, under its serde impls.
+ *
+ * `DateTime<Utc>` and `NaiveDateTime` are serialized as strings by serde,
+ * like `2023-01-01T08:00:00.500Z` and `2023-01-01T08:00:00.500`. They are decoded as `Date`,
+ * which keeps millisecond precision only.
+ *
+ * `chrono::Duration` has no serde impl, so it's not provided.
+ */
+
+function parseDate(text: string, decoder: Decoder, offset: number): Date {
+  const date = new Date(text);
+  if (Number.isNaN(date.getTime())) {
+    throw decoder.error(`invalid datetime \`${text}\``, "InvalidValue", offset);
+  }
+  return date;
+}
+
+/**
+ * `chrono::DateTime<Utc>`
+ */
+export const DateTimeUtc: Type<Date> = {
+  decode: (decoder: Decoder): Date => {
+    const offset = decoder.cursor;
+    return parseDate(Str.decode(decoder), decoder, offset);
+  },
+  encode: (data: Date, encoder: Encoder): void => {
+    Str.encode(data.toISOString(), encoder);
+  },
+};
+
+/**
+ * `chrono::NaiveDateTime`, decoded as a `Date` in UTC
+ */
+export const NaiveDateTime: Type<Date> = {
+  decode: (decoder: Decoder): Date => {
+    const offset = decoder.cursor;
+    return parseDate(`${Str.decode(decoder)}Z`, decoder, offset);
+  },
+  encode: (data: Date, encoder: Encoder): void => {
+    Str.encode(data.toISOString().slice(0, -1), encoder);
+  },
+};
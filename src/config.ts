@@ -1,6 +1,25 @@
 export class Config {
   littleEndian: boolean = true;
   fixedArrayLength: boolean = false;
+  varintEncoding: boolean = false;
+  strictEncode: boolean = false;
+  sortMapKeys: boolean = false;
+  strictBool: boolean = true;
+  tolerantVariants: boolean = false;
+  recursionLimit: number = 128;
+  limit: number = Infinity;
+  /**
+   * set the wire defaults of a bincode major version.
+   * `"1"` is bincode 1.x `bincode::serialize`: little endian, `u64` lengths and `u32` variants.
+   * `"2"` is bincode 2 `config::standard()`: little endian, varint integers, lengths and variants.
+   * Neither writes the length of fixed arrays
+   */
+  with_wire_version(version: "1" | "2"): this {
+    this.littleEndian = true;
+    this.fixedArrayLength = false;
+    this.varintEncoding = version === "2";
+    return this;
+  }
   with_big_endian(): this {
     this.littleEndian = false;
     return this;
@@ -9,6 +28,14 @@ export class Config {
     this.littleEndian = true;
     return this;
   }
+  with_varint_encoding(): this {
+    this.varintEncoding = true;
+    return this;
+  }
+  with_fixint_encoding(): this {
+    this.varintEncoding = false;
+    return this;
+  }
   write_fixed_array_length(): this {
     this.fixedArrayLength = true;
     return this;
@@ -17,4 +44,48 @@ export class Config {
     this.fixedArrayLength = false;
     return this;
   }
+  with_strict_encode(): this {
+    this.strictEncode = true;
+    return this;
+  }
+  skip_strict_encode(): this {
+    this.strictEncode = false;
+    return this;
+  }
+  with_sorted_map_keys(): this {
+    this.sortMapKeys = true;
+    return this;
+  }
+  skip_sorted_map_keys(): this {
+    this.sortMapKeys = false;
+    return this;
+  }
+  with_strict_bool(): this {
+    this.strictBool = true;
+    return this;
+  }
+  skip_strict_bool(): this {
+    this.strictBool = false;
+    return this;
+  }
+  with_tolerant_variants(): this {
+    this.tolerantVariants = true;
+    return this;
+  }
+  skip_tolerant_variants(): this {
+    this.tolerantVariants = false;
+    return this;
+  }
+  with_limit(limit: number): this {
+    this.limit = limit;
+    return this;
+  }
+  with_no_limit(): this {
+    this.limit = Infinity;
+    return this;
+  }
+  with_recursion_limit(limit: number): this {
+    this.recursionLimit = limit;
+    return this;
+  }
 }
@@ -0,0 +1,50 @@
+import { Type } from "./rust-type";
+import { Config } from "./config";
+import { Decoder } from "./decode";
+import { LazyString, StringBytes } from "./lazy-string";
+import { bytesToHex } from "./bytes";
+
+/**
+ * `JSON.stringify` replacer for decoded data: bigint as string, byte arrays as hex,
+ * maps as entry arrays, sets as arrays and lazy strings as strings
+ */
+export function jsonReplacer(_key: string, value: any): any {
+  if (typeof value === "bigint") {
+    return value.toString();
+  }
+  if (value instanceof Uint8Array || value instanceof Uint8ClampedArray) {
+    return bytesToHex(value);
+  }
+  if (ArrayBuffer.isView(value) && !(value instanceof DataView)) {
+    return Array.from(value as unknown as ArrayLike<number>);
+  }
+  if (value instanceof Map) {
+    return [...value.entries()];
+  }
+  if (value instanceof Set) {
+    return [...value];
+  }
+  if (value instanceof LazyString) {
+    return value.toString();
+  }
+  if (value instanceof StringBytes) {
+    return value.decode();
+  }
+  return value;
+}
+
+/**
+ * decode bytes with the real decoder, then stringify them for diagnostics
+ * @param bytes bincode bytes
+ * @param type type defination
+ * @param config decode config
+ * @returns indented JSON
+ */
+export function decodeToJson<Data = any>(
+  bytes: Uint8Array,
+  type: Type<Data>,
+  config?: Config
+): string {
+  const decoder = new Decoder(config).load(bytes);
+  return JSON.stringify(decoder.decodeAs(type), jsonReplacer, 2);
+}
@@ -1,21 +1,100 @@
 import { Type } from "./rust-type";
 import { Config } from "./config";
+import { BincodeDecodeError, DecodeErrorCode } from "./error";
+
+export interface TextDecoderLike {
+  decode(input: Uint8Array): string;
+}
+
+/**
+ * hooks called while decoding, shared by nested types
+ */
+export interface DecodeContext {
+  /**
+   * called with each decoded `String`, returns the string to use, e.g. an interned one
+   */
+  onString?(value: string): string;
+}
+
+/**
+ * options of `decode`, each sets the config or the decoder of one call
+ */
+export interface DecodeOptions {
+  /**
+   * base config, copied so it's not modified, default to `new Config()`
+   */
+  config?: Config;
+  /**
+   * throw `TrailingBytes` when bytes are left after the value
+   */
+  strict?: boolean;
+  /**
+   * throw `LimitExceeded` instead of reading more bytes, like `Config.with_limit`
+   */
+  maxBytes?: number;
+  /**
+   * decode unknown unit variants, like `Config.with_tolerant_variants`
+   */
+  tolerant?: boolean;
+  context?: DecodeContext;
+}
+
+let sharedTextDecoder: TextDecoderLike | undefined;
 
 export class Decoder {
   buffer: ArrayBuffer = new ArrayBuffer(0);
+  /**
+   * byte offset of the loaded bytes in `buffer`, cursor starts here
+   */
+  begin: number = 0;
+  /**
+   * byte offset of the end of loaded bytes in `buffer`
+   */
+  end: number = 0;
   cursor: number = 0;
+  marks: number[] = [];
+  depth: number = 0;
+  path: Array<string | number> = [];
   config: Config = new Config();
-  constructor(config?: Config) {
+  textDecoder?: TextDecoderLike;
+  context: DecodeContext = {};
+  /**
+   * @param config decode config
+   * @param textDecoder custom utf-8 decoder, for runtimes without a global `TextDecoder`
+   */
+  constructor(config?: Config, textDecoder?: TextDecoderLike) {
     if (config) {
       this.config = config;
     }
+    this.textDecoder = textDecoder;
   }
 
-  load(buffer: ArrayBuffer): this {
-    this.cursor = 0;
-    if (buffer instanceof ArrayBuffer) {
+  /**
+   * load bytes to decode, a view like a node `Buffer` is decoded in place,
+   * honoring its `byteOffset` and `byteLength`
+   */
+  load(buffer: ArrayBuffer | ArrayBufferView): this {
+    this.marks = [];
+    this.depth = 0;
+    this.path = [];
+    if (ArrayBuffer.isView(buffer)) {
+      this.buffer = buffer.buffer as ArrayBuffer;
+      this.begin = buffer.byteOffset;
+      this.end = buffer.byteOffset + buffer.byteLength;
+    } else if (buffer instanceof ArrayBuffer) {
       this.buffer = buffer;
+      this.begin = 0;
+      this.end = buffer.byteLength;
     }
+    this.cursor = this.begin;
+    return this;
+  }
+
+  /**
+   * set the hooks of decoding, kept across `load`
+   */
+  withContext(context: DecodeContext): this {
+    this.context = context;
     return this;
   }
 
@@ -23,22 +102,277 @@ export class Decoder {
     return type.decode(this);
   }
 
+  /**
+   * decode into `target`, reusing its objects and arrays to save allocations,
+   * e.g. for a message decoded every frame. Fields without `decodeInto` are overwritten
+   * @returns the decoded value, `target` itself when it's reused
+   */
+  decodeInto<Data = any>(type: Type<Data>, target: Data): Data {
+    if (type.decodeInto && target !== undefined && target !== null) {
+      return type.decodeInto(this, target);
+    }
+    return type.decode(this);
+  }
+
+  /**
+   * push current cursor to the mark stack, for speculative decoding
+   */
+  mark(): this {
+    this.marks.push(this.cursor);
+    return this;
+  }
+
+  /**
+   * pop the latest mark and rewind cursor to it
+   */
+  reset(): this {
+    const mark = this.marks.pop();
+    if (mark !== undefined) {
+      this.cursor = mark;
+    }
+    return this;
+  }
+
+  /**
+   * pop the latest mark and keep current cursor
+   */
+  unmark(): this {
+    this.marks.pop();
+    return this;
+  }
+
+  /**
+   * create an error at current path
+   * @param offset byte offset of the failure in `buffer`, default to cursor,
+   * it's reported relative to the loaded bytes
+   */
+  error(
+    message: string,
+    code: DecodeErrorCode = "InvalidValue",
+    offset: number = this.cursor
+  ): BincodeDecodeError {
+    return new BincodeDecodeError(
+      message,
+      code,
+      offset - this.begin,
+      [...this.path]
+    );
+  }
+
+  /**
+   * decode with a field name or element index pushed to the error path
+   */
+  decodeAt<Data>(key: string | number, type: Type<Data>): Data {
+    this.path.push(key);
+    try {
+      return type.decode(this);
+    } finally {
+      this.path.pop();
+    }
+  }
+
+  /**
+   * @returns bytes left between cursor and the end of loaded bytes
+   */
+  remaining(): number {
+    return this.end - this.cursor;
+  }
+
+  /**
+   * @returns whether bytes are left, to decode concatenated values in a loop
+   */
+  hasMore(): boolean {
+    return this.cursor < this.end;
+  }
+
+  ensure(size: number) {
+    if (this.cursor - this.begin + size > this.config.limit) {
+      throw this.error(
+        `reading ${size} bytes exceeds the limit of ${this.config.limit} bytes`,
+        "LimitExceeded"
+      );
+    }
+    if (this.cursor + size > this.end) {
+      const error = this.error(
+        `expect ${size} bytes, ${this.end - this.cursor} left`,
+        "UnexpectedEnd"
+      );
+      error.needed = this.cursor - this.begin + size;
+      throw error;
+    }
+  }
+
+  /**
+   * advance cursor by `size` bytes without reading them
+   */
+  skip(size: number): this {
+    this.ensure(size);
+    this.cursor += size;
+    return this;
+  }
+
+  /**
+   * advance cursor past a value of the type without materializing it,
+   * types without `skip` are decoded and dropped
+   */
+  skipAs(type: Type): this {
+    if (type.skip) {
+      type.skip(this);
+    } else {
+      type.decode(this);
+    }
+    return this;
+  }
+
   read(size: number): DataView {
+    this.ensure(size);
     const nextCursor = this.cursor + size;
     const view = new DataView(this.buffer, this.cursor, size);
     this.cursor = nextCursor;
     return view;
   }
 
-  readLength(): number {
-    const length = Number(
-      this.read(8).getBigUint64(0, this.config.littleEndian)
-    );
+  /**
+   * read bytes as a view on the loaded buffer, without copy
+   */
+  readBytes(size: number): Uint8Array {
+    this.ensure(size);
+    const bytes = new Uint8Array(this.buffer, this.cursor, size);
+    this.cursor += size;
+    return bytes;
+  }
+
+  /**
+   * read an unsigned varint: a single byte below 251, otherwise a tag byte
+   * (251 for u16, 252 for u32, 253 for u64, 254 for u128) followed by the integer
+   */
+  readVarint(): bigint {
+    const littleEndian = this.config.littleEndian;
+    const tag = this.read(1).getUint8(0);
+    switch (tag) {
+      case 251:
+        return BigInt(this.read(2).getUint16(0, littleEndian));
+      case 252:
+        return BigInt(this.read(4).getUint32(0, littleEndian));
+      case 253:
+        return this.read(8).getBigUint64(0, littleEndian);
+      case 254: {
+        const view = this.read(16);
+        const low = view.getBigUint64(littleEndian ? 0 : 8, littleEndian);
+        const high = view.getBigUint64(littleEndian ? 8 : 0, littleEndian);
+        return (high << 64n) | low;
+      }
+      case 255:
+        throw this.error(
+          "invalid varint tag 255",
+          "InvalidValue",
+          this.cursor - 1
+        );
+      default:
+        return BigInt(tag);
+    }
+  }
+
+  /**
+   * read a zigzag encoded signed varint
+   */
+  readSignedVarint(): bigint {
+    const zigzag = this.readVarint();
+    return zigzag % 2n === 0n ? zigzag / 2n : -(zigzag + 1n) / 2n;
+  }
+
+  /**
+   * read utf-8 bytes as string, with the custom text decoder or a shared `TextDecoder`,
+   * then pass it to `context.onString`
+   */
+  readString(size: number): string {
+    const value = this.getTextDecoder().decode(this.readBytes(size));
+    return this.context.onString ? this.context.onString(value) : value;
+  }
+
+  getTextDecoder(): TextDecoderLike {
+    return this.textDecoder ?? (sharedTextDecoder ??= new TextDecoder("utf-8"));
+  }
+
+  /**
+   * read a length of sequence, string or map. A length of elements larger than the limit
+   * or the remaining bytes is rejected before allocation
+   * @param elementSize minimum size of an element in bytes, `0` when unknown or zero-sized
+   */
+  readLength(elementSize: number = 0): number {
+    const offset = this.cursor;
+    const length = this.config.varintEncoding
+      ? Number(this.readVarint())
+      : Number(this.read(8).getBigUint64(0, this.config.littleEndian));
+    if (this.cursor - this.begin + length * elementSize > this.config.limit) {
+      throw this.error(
+        `declared length ${length} exceeds the limit of ${this.config.limit} bytes`,
+        "LimitExceeded",
+        offset
+      );
+    }
+    if (this.cursor + length * elementSize > this.end) {
+      const error = this.error(
+        `declared length ${length} exceeds ${this.end - this.cursor} remaining bytes`,
+        "UnexpectedEnd",
+        offset
+      );
+      error.needed = this.cursor - this.begin + length * elementSize;
+      throw error;
+    }
     return length;
   }
 
+  /**
+   * read the one byte tag of an `Option`, a byte other than `0` or `1` is rejected
+   * under `config.strictBool`, like `bool`
+   */
+  readOptionTag(): boolean {
+    const tag = this.read(1).getUint8(0);
+    if (tag > 1 && this.config.strictBool) {
+      throw this.error(
+        `invalid option tag ${tag}`,
+        "InvalidValue",
+        this.cursor - 1
+      );
+    }
+    return tag !== 0;
+  }
+
   readVariant(): number {
+    if (this.config.varintEncoding) {
+      return Number(this.readVarint());
+    }
     const length = this.read(4).getUint32(0, this.config.littleEndian);
     return length;
   }
 }
+
+/**
+ * decode a top-level value with options, e.g. `{ strict: true, maxBytes: 1 << 20 }`
+ */
+export function decode<Data = any>(
+  bytes: ArrayBuffer | ArrayBufferView,
+  type: Type<Data>,
+  options: DecodeOptions = {}
+): Data {
+  const config = Object.assign(new Config(), options.config);
+  if (options.maxBytes !== undefined) {
+    config.with_limit(options.maxBytes);
+  }
+  if (options.tolerant !== undefined) {
+    config.tolerantVariants = options.tolerant;
+  }
+  const decoder = new Decoder(config).load(bytes);
+  if (options.context) {
+    decoder.withContext(options.context);
+  }
+  const data = decoder.decodeAs(type);
+  if (options.strict && decoder.hasMore()) {
+    throw decoder.error(
+      `${decoder.remaining()} trailing bytes after the value`,
+      "TrailingBytes"
+    );
+  }
+  return data;
+}
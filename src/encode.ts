@@ -1,14 +1,26 @@
 import { Type } from "./rust-type";
 import { Config } from "./config";
 
+export interface TextEncoderLike {
+  encode(input: string): Uint8Array;
+}
+
+let sharedTextEncoder: TextEncoderLike | undefined;
+
 export class Encoder {
   buffer: Uint8Array = new Uint8Array(16);
   cursor: number = 0;
   config: Config = new Config();
-  constructor(config?: Config) {
+  textEncoder?: TextEncoderLike;
+  /**
+   * @param config encode config
+   * @param textEncoder custom utf-8 encoder, for runtimes without a global `TextEncoder`
+   */
+  constructor(config?: Config, textEncoder?: TextEncoderLike) {
     if (config) {
       this.config = config;
     }
+    this.textEncoder = textEncoder;
   }
 
   init(size: number = 16): this {
@@ -40,11 +52,96 @@ export class Encoder {
     return view;
   }
 
+  writeBytes(bytes: Uint8Array) {
+    const view = this.write(bytes.byteLength);
+    this.buffer.set(bytes, view.byteOffset);
+  }
+
+  writeVarint(value: bigint) {
+    const littleEndian = this.config.littleEndian;
+    if (value < 251n) {
+      this.write(1).setUint8(0, Number(value));
+    } else if (value < 1n << 16n) {
+      this.write(1).setUint8(0, 251);
+      this.write(2).setUint16(0, Number(value), littleEndian);
+    } else if (value < 1n << 32n) {
+      this.write(1).setUint8(0, 252);
+      this.write(4).setUint32(0, Number(value), littleEndian);
+    } else if (value < 1n << 64n) {
+      this.write(1).setUint8(0, 253);
+      this.write(8).setBigUint64(0, value, littleEndian);
+    } else {
+      this.write(1).setUint8(0, 254);
+      const view = this.write(16);
+      const low = value & ((1n << 64n) - 1n);
+      const high = value >> 64n;
+      view.setBigUint64(littleEndian ? 0 : 8, low, littleEndian);
+      view.setBigUint64(littleEndian ? 8 : 0, high, littleEndian);
+    }
+  }
+
+  writeSignedVarint(value: bigint) {
+    this.writeVarint(value < 0n ? -value * 2n - 1n : value * 2n);
+  }
+
+  /**
+   * encode string to utf-8 bytes, with the custom text encoder or a shared `TextEncoder`
+   */
+  encodeString(data: string): Uint8Array {
+    const textEncoder =
+      this.textEncoder ?? (sharedTextEncoder ??= new TextEncoder());
+    return textEncoder.encode(data);
+  }
+
   writeLength(length: number) {
+    if (this.config.varintEncoding) {
+      this.writeVarint(BigInt(length));
+      return;
+    }
     this.write(8).setBigUint64(0, BigInt(length), this.config.littleEndian);
   }
 
+  writeOptionTag(isSome: boolean) {
+    this.write(1).setUint8(0, Number(isSome));
+  }
+
   writeVariant(variant: number) {
+    if (this.config.varintEncoding) {
+      this.writeVarint(BigInt(variant));
+      return;
+    }
     this.write(4).setUint32(0, variant, this.config.littleEndian);
   }
 }
+
+// counts written bytes without keeping them, writes go to a scratch buffer
+class SizeCounter extends Encoder {
+  scratch: Uint8Array = new Uint8Array(16);
+
+  write(size: number): DataView {
+    this.cursor += size;
+    if (size > this.scratch.byteLength) {
+      this.scratch = new Uint8Array(size);
+    }
+    return new DataView(this.scratch.buffer, 0, size);
+  }
+
+  writeBytes(bytes: Uint8Array) {
+    this.cursor += bytes.byteLength;
+  }
+}
+
+/**
+ * compute the encoded size of a value without keeping its bytes,
+ * e.g. to allocate a buffer or enforce a frame limit before encoding
+ * @returns size in bytes, varint lengths included
+ */
+export function encodedSize<Data>(
+  data: Data,
+  type: Type<Data>,
+  config?: Config
+): number {
+  const counter = new SizeCounter(config);
+  type.encode(data, counter);
+  return counter.cursor;
+}
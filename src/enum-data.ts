@@ -8,6 +8,15 @@ export type Variant<V extends number, D = undefined> = {
   data: D;
 };
 
+/**
+ * a variant unknown to the enum type, decoded with `config.tolerantVariants`
+ */
+export type UnknownVariant = {
+  variant: number;
+  data: undefined;
+  unknown: true;
+};
+
 export function enumData<E extends EnumData, V extends E = E>(
   variant: V['variant'],
   data: V["data"]
@@ -0,0 +1,44 @@
+export type DecodeErrorCode =
+  | "UnexpectedEnd"
+  | "InvalidValue"
+  | "UnknownVariant"
+  | "SchemaMismatch"
+  | "RecursionLimit"
+  | "LimitExceeded"
+  | "TrailingBytes";
+
+/**
+ * Error thrown when decoding fails
+ */
+export class BincodeDecodeError extends Error {
+  code: DecodeErrorCode;
+  /**
+   * byte offset where the failure occurred
+   */
+  offset: number;
+  /**
+   * struct fields and tuple elements descended into, from the outermost
+   */
+  path: Array<string | number>;
+  /**
+   * rust path of the innermost `Origin` type being decoded, like `crate::model::Person`
+   */
+  origin?: string;
+  /**
+   * for `UnexpectedEnd`, bytes the failed read needed, counted from the start of the input
+   */
+  needed?: number;
+  constructor(
+    message: string,
+    code: DecodeErrorCode,
+    offset: number,
+    path: Array<string | number>
+  ) {
+    const location = path.length ? `, path \`${path.join(".")}\`` : "";
+    super(`${message} at byte ${offset}${location}`);
+    this.name = "BincodeDecodeError";
+    this.code = code;
+    this.offset = offset;
+    this.path = path;
+  }
+}
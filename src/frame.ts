@@ -0,0 +1,29 @@
+import { u32 } from "./rust-type";
+import { Config } from "./config";
+import { Decoder } from "./decode";
+import { Encoder } from "./encode";
+
+/**
+ * prepend a `u32` length to bytes, the same as a rust reader reading `u32` length then payload
+ * @param bytes payload
+ * @param config config for encoding the length
+ * @returns framed bytes
+ */
+export function frame(bytes: Uint8Array, config?: Config): Uint8Array {
+  const encoder = new Encoder(config).init(bytes.byteLength + 4);
+  u32.encode(bytes.byteLength, encoder);
+  encoder.writeBytes(bytes);
+  return encoder.buffer.slice(0, encoder.cursor);
+}
+
+/**
+ * read a `u32` length prefixed payload, the inverse of `frame`
+ * @param bytes framed bytes
+ * @param config config for decoding the length
+ * @returns payload
+ */
+export function unframe(bytes: Uint8Array, config?: Config): Uint8Array {
+  const decoder = new Decoder(config).load(bytes);
+  const length = u32.decode(decoder);
+  return decoder.readBytes(length).slice();
+}
@@ -0,0 +1,90 @@
+import { Type, u16 } from "./rust-type";
+import { Decoder } from "./decode";
+import { Encoder } from "./encode";
+
+/**
+ * `half::f16` and `half::bf16`, serde writes them as their `u16` bits.
+ * JavaScript has no half precision float, so they are decoded as number,
+ * and rounded to nearest even when encoded.
+ */
+
+const float32 = new Float32Array(1);
+const float32Bits = new Uint32Array(float32.buffer);
+
+function roundHalfEven(value: number): number {
+  const rounded = Math.round(value);
+  return Math.abs(value % 1) === 0.5 ? 2 * Math.round(value / 2) : rounded;
+}
+
+export function f16FromBits(bits: number): number {
+  const sign = bits & 0x8000 ? -1 : 1;
+  const exponent = (bits >> 10) & 0x1f;
+  const fraction = bits & 0x3ff;
+  if (exponent === 0) {
+    // zero and subnormal
+    return sign * fraction * 2 ** -24;
+  }
+  if (exponent === 0x1f) {
+    return fraction ? NaN : sign * Infinity;
+  }
+  return sign * (1 + fraction / 1024) * 2 ** (exponent - 15);
+}
+
+export function f16ToBits(value: number): number {
+  if (Number.isNaN(value)) {
+    return 0x7e00;
+  }
+  const sign = value < 0 || Object.is(value, -0) ? 0x8000 : 0;
+  const abs = Math.abs(value);
+  if (abs < 2 ** -14) {
+    // subnormal, rounding up to 1024 gives the smallest normal bits
+    return sign | roundHalfEven(abs * 2 ** 24);
+  }
+  let exponent = Math.floor(Math.log2(abs));
+  if (2 ** exponent > abs) {
+    exponent -= 1;
+  } else if (2 ** (exponent + 1) <= abs) {
+    exponent += 1;
+  }
+  let fraction = roundHalfEven((abs / 2 ** exponent - 1) * 1024);
+  if (fraction === 1024) {
+    fraction = 0;
+    exponent += 1;
+  }
+  if (exponent > 15) {
+    return sign | 0x7c00;
+  }
+  return sign | ((exponent + 15) << 10) | fraction;
+}
+
+export function bf16FromBits(bits: number): number {
+  float32Bits[0] = bits << 16;
+  return float32[0];
+}
+
+export function bf16ToBits(value: number): number {
+  if (Number.isNaN(value)) {
+    return 0x7fc0;
+  }
+  float32[0] = value;
+  const bits = float32Bits[0];
+  return ((bits + 0x7fff + ((bits >>> 16) & 1)) >>> 16) & 0xffff;
+}
+
+export const f16: Type<number> = {
+  decode: (decoder: Decoder): number => {
+    return f16FromBits(u16.decode(decoder));
+  },
+  encode: (data: number, encoder: Encoder): void => {
+    u16.encode(f16ToBits(data), encoder);
+  },
+};
+
+export const bf16: Type<number> = {
+  decode: (decoder: Decoder): number => {
+    return bf16FromBits(u16.decode(decoder));
+  },
+  encode: (data: number, encoder: Encoder): void => {
+    u16.encode(bf16ToBits(data), encoder);
+  },
+};
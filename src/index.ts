@@ -3,3 +3,11 @@ export * from "./config";
 export * from "./decode";
 export * from "./encode";
 export * from "./enum-data";
+export * from "./error";
+export * from "./lazy-string";
+export * from "./stream";
+export * from "./frame";
+export * from "./registry";
+export * from "./debug";
+export * from "./message";
+export * from "./bytes";
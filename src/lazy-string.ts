@@ -0,0 +1,50 @@
+import { TextDecoderLike } from "./decode";
+
+/**
+ * utf-8 bytes of a string in the loaded buffer, decoded on demand
+ */
+export class LazyString {
+  buffer: ArrayBuffer;
+  start: number;
+  end: number;
+  textDecoder: TextDecoderLike;
+  private decoded?: string;
+  constructor(
+    buffer: ArrayBuffer,
+    start: number,
+    end: number,
+    textDecoder: TextDecoderLike
+  ) {
+    this.buffer = buffer;
+    this.start = start;
+    this.end = end;
+    this.textDecoder = textDecoder;
+  }
+
+  /**
+   * utf-8 bytes, viewing the loaded buffer without copy
+   */
+  get bytes(): Uint8Array {
+    return new Uint8Array(this.buffer, this.start, this.end - this.start);
+  }
+
+  toString(): string {
+    return (this.decoded ??= this.textDecoder.decode(this.bytes));
+  }
+}
+
+/**
+ * utf-8 bytes of a string copied out of the loaded buffer, decoded only by `decode()`
+ */
+export class StringBytes {
+  readonly bytes: Uint8Array;
+  textDecoder: TextDecoderLike;
+  constructor(bytes: Uint8Array, textDecoder: TextDecoderLike) {
+    this.bytes = bytes;
+    this.textDecoder = textDecoder;
+  }
+
+  decode(): string {
+    return this.textDecoder.decode(this.bytes);
+  }
+}
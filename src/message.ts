@@ -0,0 +1,79 @@
+import { Type, u32 } from "./rust-type";
+import { Config } from "./config";
+import { Decoder } from "./decode";
+import { Encoder } from "./encode";
+import { EnumData, Variant } from "./enum-data";
+
+/**
+ * Dispatch top-level messages by a leading `u32` message id, each id has its own type.
+ * Messages are decoded as `Variant<Id, Data>`, the union grows with each `add`
+ */
+export class MessageDispatcher<Messages extends EnumData = never> {
+  types: Map<number, Type> = new Map();
+  config?: Config;
+  /**
+   * @param config config for decoding and encoding messages, including the id
+   */
+  constructor(config?: Config) {
+    this.config = config;
+  }
+
+  /**
+   * register the type of a message id
+   * @returns this dispatcher, typed with the new message
+   */
+  add<Id extends number, Data>(
+    id: Id,
+    type: Type<Data>
+  ): MessageDispatcher<Messages | Variant<Id, Data>> {
+    if (this.types.has(id)) {
+      throw new Error(`message id ${id} is already registered`);
+    }
+    this.types.set(id, type);
+    return this as MessageDispatcher<any>;
+  }
+
+  /**
+   * read the message id, then the message of its type
+   */
+  decodeMessage(bytes: Uint8Array): Messages {
+    const decoder = new Decoder(this.config).load(bytes);
+    return decoder.decodeAs(this.type);
+  }
+
+  /**
+   * write the message id, then the message
+   */
+  encodeMessage(message: Messages): Uint8Array {
+    return new Encoder(this.config).init().encodeAs(message, this.type);
+  }
+
+  /**
+   * the id prefixed message type, to nest messages in other types
+   */
+  get type(): Type<Messages> {
+    return {
+      decode: (decoder: Decoder): Messages => {
+        const offset = decoder.cursor;
+        const id = u32.decode(decoder);
+        const type = this.types.get(id);
+        if (type === undefined) {
+          throw decoder.error(
+            `unknown message id ${id}`,
+            "UnknownVariant",
+            offset
+          );
+        }
+        return { variant: id, data: type.decode(decoder) } as Messages;
+      },
+      encode: (message: Messages, encoder: Encoder): void => {
+        const type = this.types.get(message.variant);
+        if (type === undefined) {
+          throw new Error(`unknown message id ${message.variant}`);
+        }
+        u32.encode(message.variant, encoder);
+        type.encode(message.data, encoder);
+      },
+    };
+  }
+}
@@ -0,0 +1,129 @@
+import { Decoder } from "./decode";
+import { Lazy, Type } from "./rust-type";
+
+type RegisteredData<Types, Name> = Name extends keyof Types ? Types[Name] : any;
+
+/**
+ * A registry of named type definations, types can refer to each other by name
+ * regardless of registration order, including cycles.
+ * `Types` optionally maps type names to their data, to type lookups by name
+ */
+export class TypeRegistry<Types extends Record<string, any> = {}> {
+  types: Map<string, Type> = new Map();
+  refs: Map<string, Set<string>> = new Map();
+  prefix: string = "";
+
+  /**
+   * a view of this registry sharing its types, names are qualified as `name::Type`,
+   * so modules can register the same type name without clashes.
+   * A name starting with `::` is absolute, to refer to types of other namespaces
+   */
+  namespace(name: string): TypeRegistry {
+    const scoped = new TypeRegistry();
+    scoped.types = this.types;
+    scoped.refs = this.refs;
+    scoped.prefix = this.qualify(name);
+    return scoped;
+  }
+
+  /**
+   * @returns the full name of a type registered in this registry
+   */
+  qualify(name: string): string {
+    if (name.startsWith("::")) {
+      return name.slice(2);
+    }
+    return this.prefix ? `${this.prefix}::${name}` : name;
+  }
+
+  /**
+   * register a type, registering the same type again is a no-op
+   * @returns the registered type
+   */
+  add<Data = any>(name: string, type: Type<Data>): Type<Data> {
+    name = this.qualify(name);
+    const registered = this.types.get(name);
+    if (registered !== undefined && registered !== type) {
+      throw new Error(`type \`${name}\` is already registered`);
+    }
+    this.types.set(name, type);
+    return type;
+  }
+
+  /**
+   * register a hand-written type, replacing any registered one. It must implement `Type<Data>`:
+   * `decode` reads exactly the bytes of the value from `decoder`, and `encode` writes them to `encoder`
+   * @returns the registered type
+   */
+  override<Data = any>(name: string, type: Type<Data>): Type<Data> {
+    this.types.set(this.qualify(name), type);
+    return type;
+  }
+
+  has(name: string): boolean {
+    return this.types.has(this.qualify(name));
+  }
+
+  get<Data = any>(name: string): Type<Data> {
+    name = this.qualify(name);
+    const type = this.types.get(name);
+    if (type === undefined) {
+      throw new Error(`type \`${name}\` is not registered`);
+    }
+    return type;
+  }
+
+  /**
+   * @returns the decode function of a registered type, for dispatching by a name carried in data
+   */
+  getDecoder<Name extends string>(
+    name: Name
+  ): (decoder: Decoder) => RegisteredData<Types, Name> {
+    const type = this.get(name);
+    return (decoder: Decoder) => type.decode(decoder);
+  }
+
+  /**
+   * decode functions of all registered types, by full name
+   */
+  get decoders(): Record<string, (decoder: Decoder) => any> {
+    const decoders: Record<string, (decoder: Decoder) => any> =
+      Object.create(null);
+    for (const [name, type] of this.types) {
+      decoders[name] = (decoder: Decoder) => type.decode(decoder);
+    }
+    return decoders;
+  }
+
+  /**
+   * refer to a type by name, resolved when decoding or encoding
+   * @param from name of the referring type, reported by `check`, default to this namespace
+   */
+  ref<Data = any>(name: string, from?: string): Type<Data> {
+    const qualified = this.qualify(name);
+    const referrers = this.refs.get(qualified) ?? new Set<string>();
+    referrers.add(from === undefined ? this.prefix : this.qualify(from));
+    this.refs.set(qualified, referrers);
+    return Lazy(() => this.get<Data>(name));
+  }
+
+  /**
+   * check that every type referred by `ref` is registered, so a missing type fails
+   * at startup rather than when a value of it is first decoded
+   */
+  check(): this {
+    const missing = [...this.refs].filter(([name]) => !this.types.has(name));
+    if (missing.length > 0) {
+      const names = missing
+        .map(([name, referrers]) => {
+          const by = [...referrers].map((referrer) =>
+            referrer ? `\`${referrer}\`` : "the root namespace"
+          );
+          return `\`${name}\` referenced by ${by.join(", ")}`;
+        })
+        .join("; ");
+      throw new Error(`referred types are not registered: ${names}`);
+    }
+    return this;
+  }
+}
@@ -1,6 +1,9 @@
+import { Config } from "./config";
 import { Decoder } from "./decode";
 import { Encoder } from "./encode";
-import { EnumData, Variant } from "./enum-data";
+import { EnumData, UnknownVariant, Variant } from "./enum-data";
+import { BincodeDecodeError } from "./error";
+import { LazyString, StringBytes } from "./lazy-string";
 
 /**
  * Type Defination of Rust Types
@@ -8,13 +11,61 @@ import { EnumData, Variant } from "./enum-data";
 export interface Type<Data = any> {
   decode(decoder: Decoder): Data;
   encode(data: Data, encoder: Encoder): void;
+  /**
+   * advance the decoder past a value without materializing it, `decode` is used when absent
+   */
+  skip?(decoder: Decoder): void;
+  /**
+   * decode into an existing value, reusing its objects and arrays, `decode` is used when absent
+   * @returns the decoded value, `target` itself when it's reused
+   */
+  decodeInto?(decoder: Decoder, target: Data): Data;
 }
 
 //#region Primitive Types
+// integers are checked before encoding, DataView would wrap them silently
+function assertRange<Data extends number | bigint>(
+  data: Data,
+  min: Data,
+  max: Data,
+  name: string
+) {
+  const isInteger =
+    typeof data === "bigint" || Number.isInteger(data as number);
+  if (typeof data !== typeof min || !isInteger || data < min || data > max) {
+    throw new RangeError(`${data} is out of the range of ${name}`);
+  }
+}
+
+// skip fixed width bytes
+const skipBytes =
+  (size: number) =>
+  (decoder: Decoder): void => {
+    decoder.skip(size);
+  };
+
+// skip a fixed width integer, or a varint under varint encoding
+const skipInteger =
+  (size: number) =>
+  (decoder: Decoder): void => {
+    if (decoder.config.varintEncoding) {
+      decoder.readVarint();
+    } else {
+      decoder.skip(size);
+    }
+  };
+
 export const bool: Type<boolean> = {
   decode: (decoder: Decoder): boolean => {
-    const result = Boolean(decoder.read(1).getUint8(0));
-    return result;
+    const byte = decoder.read(1).getUint8(0);
+    if (byte > 1 && decoder.config.strictBool) {
+      throw decoder.error(
+        `invalid bool byte ${byte}`,
+        "InvalidValue",
+        decoder.cursor - 1
+      );
+    }
+    return byte !== 0;
   },
   encode: (data: boolean, encoder: Encoder): void => {
     encoder.write(1).setUint8(0, Number(data));
@@ -23,82 +74,183 @@ export const bool: Type<boolean> = {
 
 export const i8: Type<number> = {
   decode: (decoder: Decoder): number => {
-    const result = decoder.read(1).getUint8(0);
+    const result = decoder.read(1).getInt8(0);
     return result;
   },
   encode: (data: number, encoder: Encoder): void => {
-    encoder.write(1).setUint8(0, data);
+    assertRange(data, -0x80, 0x7f, "i8");
+    encoder.write(1).setInt8(0, data);
   },
+  skip: skipBytes(1),
 };
 
 export const u8: Type<number> = {
   decode: (decoder: Decoder): number => {
-    const result = decoder.read(1).getInt8(0);
+    const result = decoder.read(1).getUint8(0);
     return result;
   },
   encode: (data: number, encoder: Encoder): void => {
-    encoder.write(1).setInt8(0, data);
+    assertRange(data, 0, 0xff, "u8");
+    encoder.write(1).setUint8(0, data);
   },
+  skip: skipBytes(1),
 };
 
 export const i16: Type<number> = {
   decode: (decoder: Decoder): number => {
+    if (decoder.config.varintEncoding) {
+      return Number(decoder.readSignedVarint());
+    }
     const result = decoder.read(2).getInt16(0, decoder.config.littleEndian);
     return result;
   },
   encode: (data: number, encoder: Encoder): void => {
+    assertRange(data, -0x8000, 0x7fff, "i16");
+    if (encoder.config.varintEncoding) {
+      encoder.writeSignedVarint(BigInt(data));
+      return;
+    }
     encoder.write(2).setInt16(0, data, encoder.config.littleEndian);
   },
+  skip: skipInteger(2),
 };
 
 export const u16: Type<number> = {
   decode: (decoder: Decoder): number => {
+    if (decoder.config.varintEncoding) {
+      return Number(decoder.readVarint());
+    }
     const result = decoder.read(2).getUint16(0, decoder.config.littleEndian);
     return result;
   },
   encode: (data: number, encoder: Encoder): void => {
+    assertRange(data, 0, 0xffff, "u16");
+    if (encoder.config.varintEncoding) {
+      encoder.writeVarint(BigInt(data));
+      return;
+    }
     encoder.write(2).setUint16(0, data, encoder.config.littleEndian);
   },
+  skip: skipInteger(2),
 };
 
 export const i32: Type<number> = {
   decode: (decoder: Decoder): number => {
+    if (decoder.config.varintEncoding) {
+      return Number(decoder.readSignedVarint());
+    }
     const result = decoder.read(4).getInt32(0, decoder.config.littleEndian);
     return result;
   },
   encode: (data: number, encoder: Encoder): void => {
+    assertRange(data, -0x80000000, 0x7fffffff, "i32");
+    if (encoder.config.varintEncoding) {
+      encoder.writeSignedVarint(BigInt(data));
+      return;
+    }
     encoder.write(4).setInt32(0, data, encoder.config.littleEndian);
   },
+  skip: skipInteger(4),
 };
 
 export const u32: Type<number> = {
   decode: (decoder: Decoder): number => {
+    if (decoder.config.varintEncoding) {
+      return Number(decoder.readVarint());
+    }
     const result = decoder.read(4).getUint32(0, decoder.config.littleEndian);
     return result;
   },
   encode: (data: number, encoder: Encoder): void => {
+    assertRange(data, 0, 0xffffffff, "u32");
+    if (encoder.config.varintEncoding) {
+      encoder.writeVarint(BigInt(data));
+      return;
+    }
     encoder.write(4).setUint32(0, data, encoder.config.littleEndian);
   },
+  skip: skipInteger(4),
 };
 
 export const u64: Type<bigint> = {
   decode: (decoder: Decoder): bigint => {
+    if (decoder.config.varintEncoding) {
+      return decoder.readVarint();
+    }
     const result = decoder.read(8).getBigUint64(0, decoder.config.littleEndian);
     return result;
   },
   encode: (data: bigint, encoder: Encoder): void => {
+    assertRange(data, 0n, 0xffffffffffffffffn, "u64");
+    if (encoder.config.varintEncoding) {
+      encoder.writeVarint(data);
+      return;
+    }
     encoder.write(8).setBigUint64(0, data, encoder.config.littleEndian);
   },
+  skip: skipInteger(8),
 };
 
 export const i64: Type<bigint> = {
   decode: (decoder: Decoder): bigint => {
-    const result = decoder.read(8).getBigUint64(0, decoder.config.littleEndian);
+    if (decoder.config.varintEncoding) {
+      return decoder.readSignedVarint();
+    }
+    const result = decoder.read(8).getBigInt64(0, decoder.config.littleEndian);
     return result;
   },
   encode: (data: bigint, encoder: Encoder): void => {
+    assertRange(data, -0x8000000000000000n, 0x7fffffffffffffffn, "i64");
+    if (encoder.config.varintEncoding) {
+      encoder.writeSignedVarint(data);
+      return;
+    }
     encoder.write(8).setBigInt64(0, data, encoder.config.littleEndian);
   },
+  skip: skipInteger(8),
+};
+
+export const u128: Type<bigint> = {
+  decode: (decoder: Decoder): bigint => {
+    if (decoder.config.varintEncoding) {
+      return decoder.readVarint();
+    }
+    const littleEndian = decoder.config.littleEndian;
+    const view = decoder.read(16);
+    const low = view.getBigUint64(littleEndian ? 0 : 8, littleEndian);
+    const high = view.getBigUint64(littleEndian ? 8 : 0, littleEndian);
+    return (high << 64n) | low;
+  },
+  encode: (data: bigint, encoder: Encoder): void => {
+    assertRange(data, 0n, (1n << 128n) - 1n, "u128");
+    if (encoder.config.varintEncoding) {
+      encoder.writeVarint(data);
+      return;
+    }
+    const littleEndian = encoder.config.littleEndian;
+    const view = encoder.write(16);
+    view.setBigUint64(littleEndian ? 0 : 8, BigInt.asUintN(64, data), littleEndian);
+    view.setBigUint64(littleEndian ? 8 : 0, data >> 64n, littleEndian);
+  },
+  skip: skipInteger(16),
+};
+
+export const i128: Type<bigint> = {
+  decode: (decoder: Decoder): bigint => {
+    if (decoder.config.varintEncoding) {
+      return decoder.readSignedVarint();
+    }
+    return BigInt.asIntN(128, u128.decode(decoder));
+  },
+  encode: (data: bigint, encoder: Encoder): void => {
+    assertRange(data, -(1n << 127n), (1n << 127n) - 1n, "i128");
+    if (encoder.config.varintEncoding) {
+      encoder.writeSignedVarint(data);
+      return;
+    }
+    u128.encode(BigInt.asUintN(128, data), encoder);
+  },
+  skip: skipInteger(16),
 };
 
 export const f32: Type<number> = {
@@ -107,8 +259,14 @@ export const f32: Type<number> = {
     return result;
   },
   encode: (data: number, encoder: Encoder): void => {
+    if (Number.isNaN(data)) {
+      // the bits of NaN written by `DataView` are up to the engine, write `f32::NAN`
+      encoder.write(4).setUint32(0, 0x7fc00000, encoder.config.littleEndian);
+      return;
+    }
     encoder.write(4).setFloat32(0, data, encoder.config.littleEndian);
   },
+  skip: skipBytes(4),
 };
 
 export const f64: Type<number> = {
@@ -117,8 +275,59 @@ export const f64: Type<number> = {
     return result;
   },
   encode: (data: number, encoder: Encoder): void => {
+    if (Number.isNaN(data)) {
+      // `f64::NAN`
+      encoder
+        .write(8)
+        .setBigUint64(0, 0x7ff8000000000000n, encoder.config.littleEndian);
+      return;
+    }
     encoder.write(8).setFloat64(0, data, encoder.config.littleEndian);
   },
+  skip: skipBytes(8),
+};
+
+/**
+ * `char`, written as its 1 to 4 utf-8 bytes without a length, decoded as a one char string
+ */
+export const char: StringKeyType = {
+  decode: (decoder: Decoder): string => {
+    const offset = decoder.cursor;
+    const first = decoder.read(1).getUint8(0);
+    const size = first < 0x80 ? 1 : first >= 0xf0 ? 4 : first >= 0xe0 ? 3 : 2;
+    let codePoint = size === 1 ? first : first & (0xff >> (size + 1));
+    for (const byte of decoder.readBytes(size - 1)) {
+      if ((byte & 0xc0) !== 0x80) {
+        codePoint = -1;
+        break;
+      }
+      codePoint = (codePoint << 6) | (byte & 0x3f);
+    }
+    const minimum = [0, 0, 0x80, 0x800, 0x10000][size];
+    if (
+      (first & 0xc0) === 0x80 ||
+      first > 0xf4 ||
+      codePoint < minimum ||
+      codePoint > 0x10ffff ||
+      (codePoint >= 0xd800 && codePoint <= 0xdfff)
+    ) {
+      throw decoder.error("invalid utf-8 char", "InvalidValue", offset);
+    }
+    return String.fromCodePoint(codePoint);
+  },
+  encode: (data: string, encoder: Encoder): void => {
+    const codePoint = data.codePointAt(0);
+    if (codePoint === undefined || String.fromCodePoint(codePoint) !== data) {
+      throw new Error(`expect a single char, found \`${data}\``);
+    }
+    if (codePoint >= 0xd800 && codePoint <= 0xdfff) {
+      throw new RangeError(
+        `char U+${codePoint.toString(16).toUpperCase()} is a lone surrogate`
+      );
+    }
+    encoder.writeBytes(encoder.encodeString(data));
+  },
+  stringKey: true,
 };
 
 export const empty: Type<undefined> = {
@@ -128,12 +337,134 @@ export const empty: Type<undefined> = {
   encode: (): void => {},
 };
 
+/**
+ * `()` decoded as a token instead of `undefined`, it takes no byte either
+ * @param token decoded value, default to `null`
+ * @returns generated unit type
+ */
+export function Unit<Token = null>(token: Token = null as Token): Type<Token> {
+  return {
+    decode: (): Token => token,
+    encode: (): void => {},
+    skip: (): void => {},
+  };
+}
+
+/**
+ * wrap an integer type, the decoded value is asserted to be nonzero
+ * @param T the underlying integer type
+ * @returns generated nonzero type
+ */
+export function NonZero<Data extends number | bigint>(
+  T: Type<Data>
+): Type<Data> {
+  return {
+    decode(decoder: Decoder): Data {
+      const offset = decoder.cursor;
+      const result = T.decode(decoder);
+      if (result == 0) {
+        throw decoder.error(
+          "decoded zero for a NonZero type",
+          "InvalidValue",
+          offset
+        );
+      }
+      return result;
+    },
+    encode(data: Data, encoder: Encoder): void {
+      T.encode(data, encoder);
+    },
+  };
+}
+
+export const NonZeroI8 = NonZero(i8);
+export const NonZeroU8 = NonZero(u8);
+export const NonZeroI16 = NonZero(i16);
+export const NonZeroU16 = NonZero(u16);
+export const NonZeroI32 = NonZero(i32);
+export const NonZeroU32 = NonZero(u32);
+export const NonZeroI64 = NonZero(i64);
+export const NonZeroU64 = NonZero(u64);
+
+/**
+ * `std::num::Wrapping<T>` is encoded the same as `T`
+ */
+export function Wrapping<Data extends number | bigint>(
+  T: Type<Data>
+): Type<Data> {
+  return T;
+}
+
+/**
+ * `std::num::Saturating<T>` is encoded the same as `T`
+ */
+export const Saturating = Wrapping;
+
+/**
+ * an integer unix timestamp decoded as `Date`, the integer is kept on the wire
+ * @param T the integer type
+ * @param unit unit of the integer
+ * @param bigint whether `T` encodes a bigint, required for wrapped 64-bit and 128-bit integers,
+ * like `Branded(i64)`, it defaults to `true` for the bare `i64`, `u64`, `i128`, `u128` and their `NonZero`
+ * @returns generated type
+ */
+export function Timestamp<Data extends number | bigint>(
+  T: Type<Data>,
+  unit: "seconds" | "millis" = "seconds",
+  bigint: boolean = [i64, u64, i128, u128, NonZeroI64, NonZeroU64].includes(
+    T as Type<any>
+  )
+): Type<Date> {
+  const scale = unit === "seconds" ? 1000 : 1;
+  return {
+    decode(decoder: Decoder): Date {
+      return new Date(Number(T.decode(decoder)) * scale);
+    },
+    encode(data: Date, encoder: Encoder): void {
+      const value = Math.floor(data.getTime() / scale);
+      T.encode((bigint ? BigInt(value) : value) as Data, encoder);
+    },
+  };
+}
+
+/**
+ * a field with `#[serde(skip)]`, it's not on the wire, decoded as the default value
+ * @param defaultValue default value, or a function creates it
+ * @returns generated type
+ */
+export function Skip<Data = undefined>(
+  defaultValue?: Data | (() => Data)
+): Type<Data> {
+  return {
+    decode(): Data {
+      return typeof defaultValue === "function"
+        ? (defaultValue as () => Data)()
+        : (defaultValue as Data);
+    },
+    encode(): void {},
+  };
+}
+
+/**
+ * `PhantomData<T>` takes no byte on the wire, so every `T` shares the same type defination
+ * @returns the `empty` type
+ */
+export function PhantomData<T = any>(): Type<undefined> {
+  return empty;
+}
+
 //#endregion
 
 //#region Compound Types
-type TypeEntries<T extends Record<string, any>> = T extends any ?{
+/**
+ * fields of a struct, can be spread into another struct to inline them
+ */
+export type TypeEntries<T extends Record<string, any>> = T extends any ?{
   [K in keyof T]: [K, Type<T[K]>];
 }[keyof T][] : never;
+// `OptionalProp` types, their `None` fields are left absent
+const optionalProps = new WeakSet<Type>();
+
 /**
  * generate a rust struct type
  * @param fields fields of struct, the key is field name and the value is type defination
@@ -148,15 +479,116 @@ export function Struct<Data extends Record<string, any>>(
     decode(buffer: Decoder): Data {
       const result: Record<string, any> = {};
       for (const [field, type] of map.entries()) {
-        result[field] = type.decode(buffer);
+        const value = buffer.decodeAt(field, type);
+        if (value !== undefined || !optionalProps.has(type)) {
+          result[field] = value;
+        }
       }
       return result as Data;
     },
+    decodeInto(decoder: Decoder, target: Data): Data {
+      const result: Record<string, any> = target;
+      for (const [field, type] of map.entries()) {
+        decoder.path.push(field);
+        try {
+          const value = decoder.decodeInto(type, result[field]);
+          if (value !== undefined || !optionalProps.has(type)) {
+            result[field] = value;
+          } else {
+            delete result[field];
+          }
+        } finally {
+          decoder.path.pop();
+        }
+      }
+      return target;
+    },
     encode(data: Record<string, any>, encoder: Encoder): void {
+      if (encoder.config.strictEncode) {
+        for (const key of Object.keys(data)) {
+          if (!map.has(key)) {
+            throw new Error(`unexpected field \`${key}\` in struct`);
+          }
+        }
+        for (const [field, type] of map.entries()) {
+          if (!(field in data) && !optionalProps.has(type)) {
+            throw new Error(`missing field \`${field}\` in struct`);
+          }
+        }
+      }
       for (const [field, type] of map.entries()) {
         type.encode(data[field], encoder);
       }
     },
+    skip(decoder: Decoder): void {
+      for (const [field, type] of map.entries()) {
+        decoder.path.push(field);
+        try {
+          decoder.skipAs(type);
+        } finally {
+          decoder.path.pop();
+        }
+      }
+    },
+  };
+}
+
+/**
+ * generate a rust struct type for hot paths, like elements of a huge `Vec`.
+ * Fields are decoded directly, without the field path of decode errors
+ * @param fields fields of struct, the key is field name and the value is type defination
+ * @returns generated struct
+ */
+export function InlineStruct<Data extends Record<string, any>>(
+  fields: TypeEntries<Data>
+): Type<Data> {
+  const map = new Map(fields);
+  const names = [...map.keys()];
+  const types = [...map.values()];
+  const count = names.length;
+  return {
+    decode(decoder: Decoder): Data {
+      const result: Record<string, any> = {};
+      for (let idx = 0; idx < count; idx += 1) {
+        result[names[idx]] = types[idx].decode(decoder);
+      }
+      return result as Data;
+    },
+    decodeInto(decoder: Decoder, target: Data): Data {
+      const result: Record<string, any> = target;
+      for (let idx = 0; idx < count; idx += 1) {
+        result[names[idx]] = decoder.decodeInto(types[idx], result[names[idx]]);
+      }
+      return target;
+    },
+    encode: Struct(fields).encode,
+    skip(decoder: Decoder): void {
+      for (let idx = 0; idx < count; idx += 1) {
+        decoder.skipAs(types[idx]);
+      }
+    },
+  };
+}
+
+/**
+ * generate a rust struct type decoded into class instances
+ * @param ctor class constructor, it's called with decoded fields
+ * @param fields fields of struct, instances must have them as properties for encoding
+ * @returns generated struct
+ */
+export function Class<Instance, Fields extends Record<string, any>>(
+  ctor: new (fields: Fields) => Instance,
+  fields: TypeEntries<Fields>
+): Type<Instance> {
+  const struct = Struct<Fields>(fields);
+  return {
+    decode(decoder: Decoder): Instance {
+      return new ctor(struct.decode(decoder));
+    },
+    encode(data: Instance, encoder: Encoder): void {
+      struct.encode(data as unknown as Fields, encoder);
+    },
+    skip: struct.skip,
   };
 }
 
@@ -175,16 +607,32 @@ export function Tuple<DataTulpe extends Array<any>>(
   return {
     decode(buffer: Decoder): DataTulpe {
       const result: Array<any> = [];
-      for (const type of elements) {
-        result.push(type.decode(buffer));
+      for (const [idx, type] of elements.entries()) {
+        result.push(buffer.decodeAt(idx, type));
       }
       return result as DataTulpe;
     },
+    decodeInto(decoder: Decoder, target: DataTulpe): DataTulpe {
+      for (const [idx, type] of elements.entries()) {
+        decoder.path.push(idx);
+        try {
+          target[idx] = decoder.decodeInto(type, target[idx]);
+        } finally {
+          decoder.path.pop();
+        }
+      }
+      return target;
+    },
     encode(data: DataTulpe, encoder: Encoder): void {
       for (const [idx, type] of elements.entries()) {
         type.encode(data[idx], encoder);
       }
     },
+    skip(decoder: Decoder): void {
+      for (const type of elements) {
+        decoder.skipAs(type);
+      }
+    },
   };
 }
 
@@ -199,19 +647,57 @@ type TypeEnumDatas<UnionType extends EnumData> = UnionType extends any
 export function Enum<Data extends EnumData>(
   variants: TypeEnumDatas<Data>
 ): Type<Data> {
+  const maxVariant = Math.max(...Object.keys(variants).map(Number));
+  const fieldless = Object.values(variants as Record<number, Type>).every(
+    (type) => type === empty
+  );
+  const readVariantType = (decoder: Decoder): [number, Type | undefined] => {
+    const offset = decoder.cursor;
+    const variant = decoder.readVariant();
+    const type: Type | undefined = variants[variant];
+    if (type !== undefined) {
+      return [variant, type];
+    }
+    if (!decoder.config.tolerantVariants) {
+      throw decoder.error(
+        `unknown variant ${variant}, max known variant is ${maxVariant}`,
+        "UnknownVariant",
+        offset
+      );
+    }
+    // the length of an unknown payload is indeterminate, so only tolerate variants
+    // at the end of input, or of a fieldless enum, whose new variants are assumed unit too
+    if (!fieldless && decoder.cursor !== decoder.end) {
+      throw decoder.error(
+        `unknown variant ${variant} may carry a payload, which can't be skipped`,
+        "UnknownVariant",
+        offset
+      );
+    }
+    return [variant, undefined];
+  };
   return {
     decode(decoder: Decoder): {
       variant: number;
       data: any;
     } {
-      const variant = decoder.readVariant();
-      const type = variants[variant];
+      const [variant, type] = readVariantType(decoder);
+      if (type === undefined) {
+        const unknown: UnknownVariant = { variant, data: undefined, unknown: true };
+        return unknown;
+      }
       const data = type.decode(decoder);
       return {
         variant,
         data,
       };
     },
+    skip(decoder: Decoder): void {
+      const type = readVariantType(decoder)[1];
+      if (type !== undefined) {
+        decoder.skipAs(type);
+      }
+    },
     encode(enumData: Data, encoder: Encoder): void {
       const { variant, data } = enumData;
       encoder.writeVariant(variant);
@@ -220,24 +706,112 @@ export function Enum<Data extends EnumData>(
     },
   } as Type<Data>;
 }
+
+/**
+ * generate a rust enum type with `#[serde(tag = "...")]`, serde writes it as a struct
+ * with the variant name first, then the fields of the variant.
+ * It's decoded like serde_json does, an object with the tag field and the variant fields
+ * @param tag name of the tag field
+ * @param variants fields of each variant by variant name, like `Struct([...])`, `empty` for unit variants
+ * @returns generated enum
+ */
+export function InternallyTagged<Data extends Record<string, any>>(
+  tag: string,
+  variants: Record<string, Type>
+): Type<Data> {
+  return {
+    decode(decoder: Decoder): Data {
+      const offset = decoder.cursor;
+      const name = Str.decode(decoder);
+      const type: Type | undefined = variants[name];
+      if (type === undefined) {
+        throw decoder.error(
+          `unknown variant \`${name}\``,
+          "UnknownVariant",
+          offset
+        );
+      }
+      return Object.assign({ [tag]: name }, type.decode(decoder));
+    },
+    encode(data: Data, encoder: Encoder): void {
+      const { [tag]: name, ...fields } = data;
+      Str.encode(name, encoder);
+      variants[name].encode(fields, encoder);
+    },
+  };
+}
+
+/**
+ * generate a rust enum type with `#[serde(untagged)]`, serde writes only the variant data,
+ * so the variant can't be told from the bytes alone.
+ * @param discriminate peeks at the decoder to pick the type of the next value, the cursor is rewound after it
+ * @param select picks the type of a value to encode
+ * @returns generated enum
+ */
+export function Untagged<Data>(
+  discriminate: (decoder: Decoder) => Type<Data>,
+  select: (data: Data) => Type<Data>
+): Type<Data> {
+  return {
+    decode(decoder: Decoder): Data {
+      decoder.mark();
+      let type: Type<Data>;
+      try {
+        type = discriminate(decoder);
+      } finally {
+        decoder.reset();
+      }
+      return type.decode(decoder);
+    },
+    encode(data: Data, encoder: Encoder): void {
+      select(data).encode(data, encoder);
+    },
+  };
+}
 //#endregion
 
 //#region Collections
+// resize `target` to `count` elements in place, then decode into the kept elements
+function decodeElementsInto<Data>(
+  decoder: Decoder,
+  T: Type<Data>,
+  target: Array<Data>,
+  count: number
+): Array<Data> {
+  target.length = count;
+  for (let idx = 0; idx < count; idx += 1) {
+    target[idx] = decoder.decodeInto(T, target[idx]);
+  }
+  return target;
+}
+
+/**
+ * generate a `[T; N]` type, elements are written back-to-back without a length
+ * @param T element type, any type including structs and enums
+ * @param length length of array
+ * @returns generated array
+ */
 export function Arr<Data = any>(
   T: Type<Data>,
   length: number
 ): Type<Array<Data>> {
   return {
     decode(decoder: Decoder): Array<Data> {
-      if (decoder.config.fixedArrayLength) {
-        length = decoder.readLength();
-      }
-      const result = new Array(length);
-      for (let idx = 0; idx < length; idx += 1) {
+      const count = decoder.config.fixedArrayLength
+        ? decoder.readLength()
+        : length;
+      const result = new Array(count);
+      for (let idx = 0; idx < count; idx += 1) {
         result[idx] = T.decode(decoder);
       }
       return result;
     },
+    decodeInto(decoder: Decoder, target: Array<Data>): Array<Data> {
+      const count = decoder.config.fixedArrayLength
+        ? decoder.readLength()
+        : length;
+      return decodeElementsInto(decoder, T, target, count);
+    },
 
     encode(data: Data[], encoder: Encoder): void {
       if (encoder.config.fixedArrayLength) {
@@ -247,37 +821,95 @@ export function Arr<Data = any>(
         T.encode(dataItem, encoder);
       }
     },
+    skip(decoder: Decoder): void {
+      const count = decoder.config.fixedArrayLength
+        ? decoder.readLength()
+        : length;
+      for (let idx = 0; idx < count; idx += 1) {
+        decoder.skipAs(T);
+      }
+    },
   };
 }
 
-export const Str: Type<string> = {
+/**
+ * a type decoded as string, `AutoMap` decodes maps keyed by it as plain objects
+ */
+export type StringKeyType = Type<string> & { readonly stringKey: true };
+
+export const Str: StringKeyType = {
   decode: (decoder: Decoder): string => {
-    const stringLength = decoder.readLength();
-    const textDecoder = new TextDecoder();
-    const result = textDecoder.decode(decoder.read(stringLength));
+    const stringLength = decoder.readLength(1);
+    const result = decoder.readString(stringLength);
     return result;
   },
   encode: function (data: string, encoder: Encoder): void {
-    const bytes = new TextEncoder().encode(data);
-    const length = bytes.length;
-    encoder.writeLength(length);
-    const view = encoder.write(length);
-    for (const [idx, byte] of bytes.entries()) {
-      view.setUint8(idx, byte);
-    }
+    const bytes = encoder.encodeString(data);
+    encoder.writeLength(bytes.length);
+    encoder.writeBytes(bytes);
   },
+  skip: (decoder: Decoder): void => {
+    decoder.skip(decoder.readLength(1));
+  },
+  stringKey: true,
+};
+
+/**
+ * `String` decoded as a `LazyString`, utf-8 decoding is deferred until `toString()`
+ */
+export const LazyStr: Type<LazyString> = {
+  decode: (decoder: Decoder): LazyString => {
+    const stringLength = decoder.readLength(1);
+    const start = decoder.cursor;
+    decoder.readBytes(stringLength);
+    return new LazyString(
+      decoder.buffer,
+      start,
+      decoder.cursor,
+      decoder.getTextDecoder()
+    );
+  },
+  encode: (data: LazyString, encoder: Encoder): void => {
+    const bytes = data.bytes;
+    encoder.writeLength(bytes.length);
+    encoder.writeBytes(bytes);
+  },
+  skip: Str.skip,
+};
+
+/**
+ * `String` decoded as `StringBytes`, its utf-8 bytes are copied and never decoded
+ * unless `decode()` is called, for strings that are only hashed or forwarded
+ */
+export const StrBytes: Type<StringBytes> = {
+  decode: (decoder: Decoder): StringBytes => {
+    const stringLength = decoder.readLength(1);
+    return new StringBytes(
+      decoder.readBytes(stringLength).slice(),
+      decoder.getTextDecoder()
+    );
+  },
+  encode: (data: StringBytes, encoder: Encoder): void => {
+    encoder.writeLength(data.bytes.length);
+    encoder.writeBytes(data.bytes);
+  },
+  skip: Str.skip,
 };
 
 export function Vec<Data = any>(T: Type<Data>): Type<Array<Data>> {
   return {
     decode(decoder): Array<any> {
       const length = decoder.readLength();
-      const result = new Array(length);
+      // elements may take no bytes, so only preallocate what the remaining bytes can hold
+      const result = length <= decoder.remaining() ? new Array(length) : [];
       for (let idx = 0; idx < length; idx += 1) {
         result[idx] = T.decode(decoder);
       }
       return result;
     },
+    decodeInto(decoder: Decoder, target: Array<Data>): Array<Data> {
+      return decodeElementsInto(decoder, T, target, decoder.readLength());
+    },
     encode(data, encoder) {
       const length = data.length;
       encoder.writeLength(length);
@@ -285,9 +917,206 @@ export function Vec<Data = any>(T: Type<Data>): Type<Array<Data>> {
         T.encode(dataItem, encoder);
       }
     },
+    skip(decoder: Decoder): void {
+      const length = decoder.readLength();
+      for (let idx = 0; idx < length; idx += 1) {
+        decoder.skipAs(T);
+      }
+    },
   };
 }
 
+type NumberArray =
+  | Int8Array
+  | Uint8Array
+  | Uint8ClampedArray
+  | Int16Array
+  | Uint16Array
+  | Int32Array
+  | Uint32Array
+  | Float32Array
+  | Float64Array;
+
+interface NumberArrayConstructor<A extends NumberArray> {
+  readonly BYTES_PER_ELEMENT: number;
+  new (length: number): A;
+  new (buffer: ArrayBufferLike, byteOffset?: number, length?: number): A;
+}
+
+const hostLittleEndian = new Uint8Array(new Uint16Array([1]).buffer)[0] === 1;
+
+// decode and encode typed array elements, without the length
+function TypedElements<A extends NumberArray>(
+  ArrayType: NumberArrayConstructor<A>,
+  T: Type<number>
+) {
+  const size = ArrayType.BYTES_PER_ELEMENT;
+  const isFloat = [Float32Array, Float64Array].some(
+    (FloatArray) => FloatArray === (ArrayType as unknown)
+  );
+  // integers wider than a byte are not fixed width under varint encoding
+  const isBulk = (config: Config) =>
+    config.littleEndian === hostLittleEndian &&
+    (size === 1 || isFloat || !config.varintEncoding);
+  return {
+    // a varint integer takes at least a byte, any other element its full size
+    readLength(decoder: Decoder): number {
+      const varint = decoder.config.varintEncoding && !isFloat;
+      return decoder.readLength(varint ? 1 : size);
+    },
+    decode(decoder: Decoder, length: number): A {
+      if (isBulk(decoder.config)) {
+        const offset = decoder.cursor;
+        const bytes = decoder.readBytes(length * size);
+        if (offset % size === 0) {
+          // aligned, view the loaded buffer then copy
+          return new ArrayType(decoder.buffer, offset, length).slice() as A;
+        }
+        // unaligned, copy bytes to a fresh aligned buffer
+        return new ArrayType(bytes.slice().buffer);
+      }
+      const result: NumberArray = new ArrayType(length);
+      for (let idx = 0; idx < length; idx += 1) {
+        result[idx] = T.decode(decoder);
+      }
+      return result as A;
+    },
+    encode(data: A, encoder: Encoder): void {
+      if (isBulk(encoder.config)) {
+        encoder.writeBytes(
+          new Uint8Array(data.buffer, data.byteOffset, data.byteLength)
+        );
+        return;
+      }
+      for (const dataItem of data) {
+        T.encode(dataItem, encoder);
+      }
+    },
+    skip(decoder: Decoder, length: number): void {
+      if (isBulk(decoder.config)) {
+        decoder.skip(length * size);
+        return;
+      }
+      for (let idx = 0; idx < length; idx += 1) {
+        decoder.skipAs(T);
+      }
+    },
+  };
+}
+
+/**
+ * generate a `Vec<T>` type decoded as a typed array, bytes are copied in bulk
+ * when the config endianness matches the host
+ * @param ArrayType typed array constructor, like `Float32Array`
+ * @param T element type, used when bytes can't be copied directly
+ * @returns generated vec
+ */
+export function TypedVec<A extends NumberArray>(
+  ArrayType: NumberArrayConstructor<A>,
+  T: Type<number>
+): Type<A> {
+  const elements = TypedElements(ArrayType, T);
+  return {
+    decode(decoder: Decoder): A {
+      return elements.decode(decoder, elements.readLength(decoder));
+    },
+    encode(data: A, encoder: Encoder): void {
+      encoder.writeLength(data.length);
+      elements.encode(data, encoder);
+    },
+    skip(decoder: Decoder): void {
+      elements.skip(decoder, elements.readLength(decoder));
+    },
+  };
+}
+
+/**
+ * generate a `[T; N]` type decoded as a typed array, like `TypedVec`
+ * @param ArrayType typed array constructor, like `Int8Array`
+ * @param T element type, used when bytes can't be copied directly
+ * @param length length of array
+ * @returns generated array
+ */
+export function TypedArr<A extends NumberArray>(
+  ArrayType: NumberArrayConstructor<A>,
+  T: Type<number>,
+  length: number
+): Type<A> {
+  const elements = TypedElements(ArrayType, T);
+  return {
+    decode(decoder: Decoder): A {
+      return elements.decode(
+        decoder,
+        decoder.config.fixedArrayLength ? elements.readLength(decoder) : length
+      );
+    },
+    encode(data: A, encoder: Encoder): void {
+      if (encoder.config.fixedArrayLength) {
+        encoder.writeLength(length);
+      }
+      elements.encode(data, encoder);
+    },
+    skip(decoder: Decoder): void {
+      elements.skip(
+        decoder,
+        decoder.config.fixedArrayLength ? elements.readLength(decoder) : length
+      );
+    },
+  };
+}
+
+/**
+ * `Vec<u8>` or `[u8]`, decoded as `Uint8Array`
+ */
+export const Bytes: Type<Uint8Array> = TypedVec(Uint8Array, u8);
+
+/**
+ * `[u8; N]`, decoded as a `Uint8Array` of `N` bytes with a single copy
+ * @param length length of array
+ * @returns generated array
+ */
+export function ByteArr(length: number): Type<Uint8Array> {
+  return TypedArr(Uint8Array, u8, length);
+}
+
+/**
+ * `Cow<'a, B>` is encoded the same as the owned `B`
+ * @param B the borrowed type, like `Str` or `Bytes`
+ * @returns the same type defination
+ */
+export function Cow<Data = any>(B: Type<Data>): Type<Data> {
+  return B;
+}
+
+/**
+ * order keys like rust's `Ord`: numbers and bigints by value, strings by code point,
+ * `false` before `true`, tuples and arrays element by element
+ */
+function compareKeys(a: any, b: any): number {
+  if (typeof a === "string" && typeof b === "string") {
+    const aPoints = Array.from(a, (char) => char.codePointAt(0)!);
+    const bPoints = Array.from(b, (char) => char.codePointAt(0)!);
+    return compareKeys(aPoints, bPoints);
+  }
+  if (Array.isArray(a) && Array.isArray(b)) {
+    for (let idx = 0; idx < a.length && idx < b.length; idx += 1) {
+      const order = compareKeys(a[idx], b[idx]);
+      if (order !== 0) {
+        return order;
+      }
+    }
+    return a.length - b.length;
+  }
+  if (ArrayBuffer.isView(a) && ArrayBuffer.isView(b)) {
+    return compareKeys(Array.from(a as Uint8Array), Array.from(b as Uint8Array));
+  }
+  return a < b ? -1 : a > b ? 1 : 0;
+}
+
+/**
+ * `HashMap<K, V>` decoded as `Map`, entries keep the wire order.
+ * With `config.sortMapKeys`, entries are encoded sorted by key, like a `BTreeMap`
+ */
 export function HashMap<DataK = any, DataV = any>(
   K: Type<DataK>,
   V: Type<DataV>
@@ -306,14 +1135,80 @@ export function HashMap<DataK = any, DataV = any>(
     encode(data, encoder) {
       const length = data.size;
       encoder.writeLength(length);
-      for (const [key, value] of data.entries()) {
+      const entries = encoder.config.sortMapKeys
+        ? [...data.entries()].sort(([a], [b]) => compareKeys(a, b))
+        : data.entries();
+      for (const [key, value] of entries) {
         K.encode(key, encoder);
         V.encode(value, encoder);
       }
     },
+    skip(decoder: Decoder): void {
+      const length = decoder.readLength();
+      for (let idx = 0; idx < length; idx += 1) {
+        decoder.skipAs(K).skipAs(V);
+      }
+    },
+  };
+}
+
+/**
+ * `HashMap<String, V>` decoded as a plain object, maps with other key types need `HashMap`
+ * @param V value type
+ * @param K key type, decoded as string
+ * @returns generated map
+ */
+export function ObjectMap<DataV = any>(
+  V: Type<DataV>,
+  K: Type<string> = Str
+): Type<Record<string, DataV>> {
+  return {
+    decode(decoder: Decoder): Record<string, DataV> {
+      // no prototype, so keys like `__proto__` are plain properties
+      const result: Record<string, DataV> = Object.create(null);
+      const length = decoder.readLength();
+      for (let idx = 0; idx < length; idx += 1) {
+        const key = K.decode(decoder);
+        result[key] = V.decode(decoder);
+      }
+      return result;
+    },
+    encode(data: Record<string, DataV>, encoder: Encoder): void {
+      const keys = Object.keys(data);
+      if (encoder.config.sortMapKeys) {
+        keys.sort(compareKeys);
+      }
+      encoder.writeLength(keys.length);
+      for (const key of keys) {
+        K.encode(key, encoder);
+        V.encode(data[key], encoder);
+      }
+    },
+    skip: HashMap(K, V).skip,
   };
 }
 
+/**
+ * a map decoded as a plain object when keys are `StringKeyType`, like `Str` and `char`,
+ * otherwise as `Map`. Use `HashMap` or `ObjectMap` to choose explicitly
+ * @param K key type
+ * @param V value type
+ * @returns generated map
+ */
+export function AutoMap<DataV = any>(
+  K: StringKeyType,
+  V: Type<DataV>
+): Type<Record<string, DataV>>;
+export function AutoMap<DataK = any, DataV = any>(
+  K: Type<DataK>,
+  V: Type<DataV>
+): Type<Map<DataK, DataV>>;
+export function AutoMap(K: Type, V: Type): Type {
+  return (K as Partial<StringKeyType>).stringKey === true
+    ? ObjectMap(V, K)
+    : HashMap(K, V);
+}
+
 export function HashSet<DataK = any>(K: Type<DataK>): Type<Set<DataK>> {
   return {
     decode(decoder: Decoder): Set<DataK> {
@@ -328,17 +1223,573 @@ export function HashSet<DataK = any>(K: Type<DataK>): Type<Set<DataK>> {
     encode(data, encoder) {
       const length = data.size;
       encoder.writeLength(length);
-      for (const key of data) {
+      const keys = encoder.config.sortMapKeys
+        ? [...data].sort(compareKeys)
+        : data;
+      for (const key of keys) {
         K.encode(key, encoder);
       }
     },
+    skip: Vec(K).skip,
+  };
+}
+
+/**
+ * `BTreeMap<K, V>` shares the layout of `HashMap<K, V>`, entries are decoded in wire order, which is sorted by key
+ */
+export const BTreeMap = HashMap;
+
+/**
+ * `BTreeSet<K>` shares the layout of `HashSet<K>`, keys are decoded in wire order, which is sorted
+ */
+export const BTreeSet = HashSet;
+
+/**
+ * `BinaryHeap<T>` is written as a sequence in the heap's internal order, which is not sorted
+ */
+export const BinaryHeap = Vec;
+//#endregion
+
+//#region Range
+export type Range<T> = {
+  start: T;
+  end: T;
+};
+
+/**
+ * `Range<T>`, serde writes `start` then `end`
+ */
+export function Range<Data = any>(T: Type<Data>): Type<Range<Data>> {
+  return Struct<Range<Data>>([
+    ["start", T],
+    ["end", T],
+  ]);
+}
+
+/**
+ * `RangeInclusive<T>`, serde writes `start` then `end` too, the `exhausted` flag is not serialized
+ */
+export const RangeInclusive = Range;
+//#endregion
+
+//#region Net
+function parseIpv4(text: string): number[] {
+  const octets = text.split(".");
+  if (
+    octets.length !== 4 ||
+    octets.some((octet) => !/^\d{1,3}$/.test(octet) || Number(octet) > 255)
+  ) {
+    throw new Error(`invalid ipv4 address \`${text}\``);
+  }
+  return octets.map(Number);
+}
+
+function parseIpv6(text: string): number[] {
+  const invalid = () => new Error(`invalid ipv6 address \`${text}\``);
+  const parseGroups = (part: string): number[] =>
+    part === ""
+      ? []
+      : part.split(":").flatMap((group): number[] => {
+          if (group.includes(".")) {
+            const [a, b, c, d] = parseIpv4(group);
+            return [(a << 8) | b, (c << 8) | d];
+          }
+          if (!/^[0-9a-fA-F]{1,4}$/.test(group)) {
+            throw invalid();
+          }
+          return [parseInt(group, 16)];
+        });
+  const parts = text.split("::");
+  if (parts.length > 2) {
+    throw invalid();
+  }
+  const head = parseGroups(parts[0]);
+  const tail = parts.length === 2 ? parseGroups(parts[1]) : [];
+  const fill = 8 - head.length - tail.length;
+  if (parts.length === 2 ? fill < 1 : fill !== 0) {
+    throw invalid();
+  }
+  const segments = [...head, ...new Array<number>(fill).fill(0), ...tail];
+  return segments.flatMap((segment) => [segment >> 8, segment & 0xff]);
+}
+
+function formatIpv6(octets: Uint8Array): string {
+  const segments: number[] = [];
+  for (let idx = 0; idx < 16; idx += 2) {
+    segments.push((octets[idx] << 8) | octets[idx + 1]);
+  }
+  if (
+    segments.slice(0, 5).every((segment) => segment === 0) &&
+    segments[5] === 0xffff
+  ) {
+    return `::ffff:${Array.from(octets.subarray(12)).join(".")}`;
+  }
+  // find the longest run of zero segments, it's compressed as `::`
+  let zeroStart = -1;
+  let zeroLength = 0;
+  for (let idx = 0; idx < 8; ) {
+    let end = idx;
+    while (end < 8 && segments[end] === 0) {
+      end += 1;
+    }
+    if (end - idx > zeroLength) {
+      zeroStart = idx;
+      zeroLength = end - idx;
+    }
+    idx = end + 1;
+  }
+  const hex = (from: number, to: number) =>
+    segments
+      .slice(from, to)
+      .map((segment) => segment.toString(16))
+      .join(":");
+  if (zeroLength < 2) {
+    return hex(0, 8);
+  }
+  return `${hex(0, zeroStart)}::${hex(zeroStart + zeroLength, 8)}`;
+}
+
+/**
+ * `std::net::Ipv4Addr`, decoded as a string like `"127.0.0.1"`
+ */
+export const Ipv4Addr: Type<string> = {
+  decode: (decoder: Decoder): string => {
+    return Array.from(decoder.readBytes(4)).join(".");
+  },
+  encode: (data: string, encoder: Encoder): void => {
+    encoder.writeBytes(new Uint8Array(parseIpv4(data)));
+  },
+};
+
+/**
+ * `std::net::Ipv6Addr`, decoded as a string like `"::1"`
+ */
+export const Ipv6Addr: Type<string> = {
+  decode: (decoder: Decoder): string => {
+    return formatIpv6(decoder.readBytes(16));
+  },
+  encode: (data: string, encoder: Encoder): void => {
+    encoder.writeBytes(new Uint8Array(parseIpv6(data)));
+  },
+};
+
+/**
+ * `std::net::IpAddr`, an enum of `V4` and `V6`, decoded as a string
+ */
+export const IpAddr: Type<string> = {
+  decode: (decoder: Decoder): string => {
+    const offset = decoder.cursor;
+    const variant = decoder.readVariant();
+    switch (variant) {
+      case 0:
+        return Ipv4Addr.decode(decoder);
+      case 1:
+        return Ipv6Addr.decode(decoder);
+      default:
+        throw decoder.error(
+          `unknown IpAddr variant ${variant}`,
+          "UnknownVariant",
+          offset
+        );
+    }
+  },
+  encode: (data: string, encoder: Encoder): void => {
+    if (data.includes(":")) {
+      encoder.writeVariant(1);
+      Ipv6Addr.encode(data, encoder);
+    } else {
+      encoder.writeVariant(0);
+      Ipv4Addr.encode(data, encoder);
+    }
+  },
+};
+
+/**
+ * `std::net::SocketAddr`, decoded as a string like `"127.0.0.1:8080"` or `"[::1]:8080"`
+ */
+export const SocketAddr: Type<string> = {
+  decode: (decoder: Decoder): string => {
+    const offset = decoder.cursor;
+    const variant = decoder.readVariant();
+    switch (variant) {
+      case 0:
+        return `${Ipv4Addr.decode(decoder)}:${u16.decode(decoder)}`;
+      case 1:
+        return `[${Ipv6Addr.decode(decoder)}]:${u16.decode(decoder)}`;
+      default:
+        throw decoder.error(
+          `unknown SocketAddr variant ${variant}`,
+          "UnknownVariant",
+          offset
+        );
+    }
+  },
+  encode: (data: string, encoder: Encoder): void => {
+    const separator = data.lastIndexOf(":");
+    const ip = data.slice(0, separator);
+    const port = data.slice(separator + 1);
+    if (separator < 0 || !/^\d{1,5}$/.test(port) || Number(port) > 0xffff) {
+      throw new Error(`invalid socket address \`${data}\``);
+    }
+    if (ip.startsWith("[") && ip.endsWith("]")) {
+      encoder.writeVariant(1);
+      Ipv6Addr.encode(ip.slice(1, -1), encoder);
+    } else {
+      encoder.writeVariant(0);
+      Ipv4Addr.encode(ip, encoder);
+    }
+    u16.encode(Number(port), encoder);
+  },
+};
+//#endregion
+
+//#region Pointers
+/**
+ * `Box<T>` is encoded the same as `T`
+ * @param T the boxed type
+ * @returns the same type defination
+ */
+export function Box<Data = any>(T: Type<Data>): Type<Data> {
+  return T;
+}
+
+/**
+ * `Rc<T>` is encoded the same as `T`, with serde's `rc` feature
+ */
+export const Rc = Box;
+
+/**
+ * `Arc<T>` is encoded the same as `T`, with serde's `rc` feature
+ */
+export const Arc = Box;
+
+/**
+ * `Mutex<T>` is encoded the same as `T`, serde writes the guarded value
+ */
+export const Mutex = Box;
+
+/**
+ * `RwLock<T>` is encoded the same as `T`, serde writes the guarded value
+ */
+export const RwLock = Box;
+
+/**
+ * refer to a type defination lazily, for recursive types,
+ * decoding throws when nested deeper than `config.recursionLimit`
+ * @param getType a function returns the referred type
+ * @returns generated type
+ */
+export function Lazy<Data = any>(getType: () => Type<Data>): Type<Data> {
+  return {
+    decode(decoder: Decoder): Data {
+      if (decoder.depth >= decoder.config.recursionLimit) {
+        throw decoder.error("recursion limit exceeded", "RecursionLimit");
+      }
+      decoder.depth += 1;
+      try {
+        return getType().decode(decoder);
+      } finally {
+        decoder.depth -= 1;
+      }
+    },
+    encode(data: Data, encoder: Encoder): void {
+      getType().encode(data, encoder);
+    },
+    skip(decoder: Decoder): void {
+      if (decoder.depth >= decoder.config.recursionLimit) {
+        throw decoder.error("recursion limit exceeded", "RecursionLimit");
+      }
+      decoder.depth += 1;
+      try {
+        decoder.skipAs(getType());
+      } finally {
+        decoder.depth -= 1;
+      }
+    },
+  };
+}
+
+export interface Cons<Item> {
+  variant: 1;
+  data: [Item, ConsList<Item>];
+}
+export type ConsList<Item> = Variant<0> | Cons<Item>;
+
+/**
+ * a linked list enum like `enum List { Nil, Cons(T, Box<List>) }`, decoded and encoded with a loop,
+ * so deep lists don't overflow the call stack
+ * @param T item type
+ * @returns generated list
+ */
+export function ConsList<Item = any>(T: Type<Item>): Type<ConsList<Item>> {
+  return {
+    decode(decoder: Decoder): ConsList<Item> {
+      const items: Item[] = [];
+      for (;;) {
+        const offset = decoder.cursor;
+        const variant = decoder.readVariant();
+        if (variant === 0) {
+          break;
+        }
+        if (variant !== 1) {
+          throw decoder.error(
+            `unknown variant ${variant}, max known variant is 1`,
+            "UnknownVariant",
+            offset
+          );
+        }
+        items.push(T.decode(decoder));
+      }
+      let list: ConsList<Item> = { variant: 0, data: undefined };
+      for (let idx = items.length - 1; idx >= 0; idx -= 1) {
+        list = { variant: 1, data: [items[idx], list] };
+      }
+      return list;
+    },
+    encode(data: ConsList<Item>, encoder: Encoder): void {
+      let node = data;
+      while (node.variant === 1) {
+        encoder.writeVariant(1);
+        T.encode(node.data[0], encoder);
+        node = node.data[1];
+      }
+      encoder.writeVariant(0);
+    },
+  };
+}
+//#endregion
+
+//#region Readonly
+export type DeepReadonly<T> = T extends ArrayBufferView
+  ? T
+  : T extends Map<infer K, infer V>
+  ? ReadonlyMap<DeepReadonly<K>, DeepReadonly<V>>
+  : T extends Set<infer K>
+  ? ReadonlySet<DeepReadonly<K>>
+  : T extends object
+  ? { readonly [K in keyof T]: DeepReadonly<T[K]> }
+  : T;
+
+/**
+ * declare decoded data as deeply readonly, arrays and tuples become readonly,
+ * maps and sets become `ReadonlyMap` and `ReadonlySet`. Decoded objects are unchanged.
+ * @param T the type to wrap
+ * @returns the same type defination
+ */
+export function Readonly<Data = any>(T: Type<Data>): Type<DeepReadonly<Data>> {
+  return T as unknown as Type<DeepReadonly<Data>>;
+}
+//#endregion
+
+//#region Brand
+export type Brand<T, Name extends string> = T & { readonly __brand: Name };
+
+/**
+ * declare decoded data as a branded type, for newtype wrappers like `struct UserId(u64)`,
+ * so different brands don't unify. Decoded values are unchanged.
+ * @param name brand name
+ * @param T the wrapped type
+ * @returns the same type defination
+ */
+export function Branded<Name extends string, Data = any>(
+  name: Name,
+  T: Type<Data>
+): Type<Brand<Data, Name>> {
+  return T as Type<Brand<Data, Name>>;
+}
+//#endregion
+
+//#region Origin
+/**
+ * note the rust definition of a type, decode errors inside it carry it as `origin`
+ * @param origin rust path, like `crate::model::Person`, or a source location
+ * @param T the type
+ * @returns generated type
+ */
+export function Origin<Data = any>(origin: string, T: Type<Data>): Type<Data> {
+  const trace = (error: unknown) => {
+    if (error instanceof BincodeDecodeError && error.origin === undefined) {
+      error.origin = origin;
+      error.message += `, in \`${origin}\``;
+    }
+    return error;
+  };
+  return {
+    decode(decoder: Decoder): Data {
+      try {
+        return T.decode(decoder);
+      } catch (error) {
+        throw trace(error);
+      }
+    },
+    encode(data: Data, encoder: Encoder): void {
+      T.encode(data, encoder);
+    },
+    skip(decoder: Decoder): void {
+      try {
+        decoder.skipAs(T);
+      } catch (error) {
+        throw trace(error);
+      }
+    },
+  };
+}
+//#endregion
+
+//#region Schema
+/**
+ * a stable 64-bit hash of a type layout description, like `Person{name:String,age:u32}`.
+ * It's FNV-1a 64 over the utf-8 bytes of the description, so a rust producer can compute the same value.
+ * @param layout layout description
+ * @returns hash
+ */
+export function schemaHash(layout: string): bigint {
+  let hash = 0xcbf29ce484222325n;
+  for (const byte of new TextEncoder().encode(layout)) {
+    hash ^= BigInt(byte);
+    hash = (hash * 0x100000001b3n) & 0xffffffffffffffffn;
+  }
+  return hash;
+}
+
+/**
+ * prefix a type with a schema hash, decoding throws before the body when the hash mismatches.
+ * The producer must write the same hash in front of the body, as a `u32` when `hash` is a number,
+ * or as a `u64` when `hash` is a bigint.
+ * @param hash expected schema hash
+ * @param T the body type
+ * @returns generated type
+ */
+export function SchemaChecked<Data = any>(
+  hash: number | bigint,
+  T: Type<Data>
+): Type<Data> {
+  return {
+    decode(decoder: Decoder): Data {
+      const offset = decoder.cursor;
+      const found =
+        typeof hash === "bigint" ? u64.decode(decoder) : u32.decode(decoder);
+      if (found !== hash) {
+        throw decoder.error(
+          `schema mismatch, expect hash ${hash.toString(16)}, found ${found.toString(16)}`,
+          "SchemaMismatch",
+          offset
+        );
+      }
+      return T.decode(decoder);
+    },
+    encode(data: Data, encoder: Encoder): void {
+      if (typeof hash === "bigint") {
+        u64.encode(hash, encoder);
+      } else {
+        u32.encode(hash, encoder);
+      }
+      T.encode(data, encoder);
+    },
+  };
+}
+
+/**
+ * a runtime schema, like a `zod` schema, `parse` returns valid data or throws
+ */
+export interface Schema<Data> {
+  parse(data: unknown): Data;
+}
+
+/**
+ * validate decoded values against a runtime schema, a rejected value throws `InvalidValue`
+ * @param schema schema mirroring the decoded data, e.g. `z.object({ id: z.bigint() })`
+ * @param T the decoded type
+ * @returns generated type, typed by the schema
+ */
+export function Validated<Data = any>(
+  schema: Schema<Data>,
+  T: Type<any>
+): Type<Data> {
+  return {
+    decode(decoder: Decoder): Data {
+      const offset = decoder.cursor;
+      const data = T.decode(decoder);
+      try {
+        return schema.parse(data);
+      } catch (e) {
+        throw decoder.error(
+          `validation failed: ${e instanceof Error ? e.message : e}`,
+          "InvalidValue",
+          offset
+        );
+      }
+    },
+    encode: T.encode,
+    skip: T.skip,
   };
 }
 //#endregion
 
 //#region Option and Result
-export const Option = <DataT = any>(T: Type<DataT>) =>
-  Enum<Variant<0> | Variant<1, DataT>>([empty, T]);
+// skip the tag of an option, then the value if it's `Some`
+function OptionSkip(T: Type) {
+  return (decoder: Decoder): void => {
+    if (decoder.readOptionTag()) {
+      decoder.skipAs(T);
+    }
+  };
+}
+
+export const Option = <DataT = any>(
+  T: Type<DataT>
+): Type<Variant<0> | Variant<1, DataT>> => ({
+  decode(decoder: Decoder): Variant<0> | Variant<1, DataT> {
+    return decoder.readOptionTag() ? Some(T.decode(decoder)) : None();
+  },
+  encode(data: Variant<0> | Variant<1, DataT>, encoder: Encoder): void {
+    encoder.writeOptionTag(data.variant === 1);
+    if (data.variant === 1) {
+      T.encode(data.data, encoder);
+    }
+  },
+  skip: OptionSkip(T),
+});
+
+function OptionOr<DataT, DataNone extends null | undefined>(
+  T: Type<DataT>,
+  none: DataNone
+): Type<DataT | DataNone> {
+  return {
+    decode(decoder: Decoder): DataT | DataNone {
+      return decoder.readOptionTag() ? T.decode(decoder) : none;
+    },
+    encode(data: DataT | DataNone, encoder: Encoder): void {
+      const isSome = data !== none && data !== undefined;
+      encoder.writeOptionTag(isSome);
+      if (isSome) {
+        T.encode(data as DataT, encoder);
+      }
+    },
+    skip: OptionSkip(T),
+  };
+}
+
+/**
+ * `Option<T>` decoded as `T | null`
+ */
+export const Nullable = <DataT = any>(T: Type<DataT>) =>
+  OptionOr<DataT, null>(T, null);
+
+/**
+ * `Option<T>` decoded as `T | undefined`
+ */
+export const Optional = <DataT = any>(T: Type<DataT>) =>
+  OptionOr<DataT, undefined>(T, undefined);
+
+/**
+ * `Option<T>` as an optional struct field `field?: T`, a `None` field is left absent instead of `undefined`
+ */
+export const OptionalProp = <DataT = any>(T: Type<DataT>) => {
+  const type = Optional(T);
+  optionalProps.add(type);
+  return type;
+};
 export function None(): Variant<0> {
   return {
     variant: 0,
@@ -0,0 +1,271 @@
+import { Type } from "./rust-type";
+import { Config } from "./config";
+import { Decoder } from "./decode";
+import { Encoder } from "./encode";
+import { BincodeDecodeError } from "./error";
+
+const length: Type<number> = {
+  decode: (decoder) => decoder.readLength(),
+  encode: (data, encoder) => encoder.writeLength(data),
+};
+
+export class StreamDecoder {
+  reader: ReadableStreamDefaultReader<Uint8Array>;
+  /**
+   * received bytes, `pending[offset..filled]` are not decoded yet
+   */
+  pending: Uint8Array = new Uint8Array(0);
+  offset: number = 0;
+  filled: number = 0;
+  decoder: Decoder;
+  signal?: AbortSignal;
+  /**
+   * @param signal aborting it cancels the stream, discards pending bytes and releases the reader
+   */
+  constructor(
+    stream: ReadableStream<Uint8Array>,
+    config?: Config,
+    signal?: AbortSignal
+  ) {
+    this.reader = stream.getReader();
+    this.decoder = new Decoder(config);
+    this.signal = signal;
+    if (signal?.aborted) {
+      this.abort();
+    } else {
+      signal?.addEventListener("abort", this.abort, { once: true });
+    }
+  }
+
+  private abort = () => {
+    this.pending = new Uint8Array(0);
+    this.offset = 0;
+    this.filled = 0;
+    // a pending read resolves as done once canceled
+    this.reader
+      .cancel(this.signal?.reason)
+      .catch(() => {})
+      .then(() => this.reader.releaseLock());
+  };
+
+  /**
+   * read next chunk from the stream into pending bytes
+   * @returns false if the stream is done
+   */
+  async pull(): Promise<boolean> {
+    if (this.signal?.aborted) {
+      throw this.signal.reason;
+    }
+    const { done, value } = await this.reader.read();
+    if (this.signal?.aborted) {
+      throw this.signal.reason;
+    }
+    if (done) {
+      return false;
+    }
+    const unread = this.filled - this.offset;
+    if (this.filled + value.byteLength > this.pending.byteLength) {
+      // compact decoded bytes away, grow by doubling when still short
+      const needed = unread + value.byteLength;
+      const pending =
+        needed > this.pending.byteLength
+          ? new Uint8Array(Math.max(needed, 2 * this.pending.byteLength))
+          : this.pending;
+      pending.set(this.pending.subarray(this.offset, this.filled), 0);
+      this.pending = pending;
+      this.offset = 0;
+      this.filled = unread;
+    }
+    this.pending.set(value, this.filled);
+    this.filled += value.byteLength;
+    return true;
+  }
+
+  /**
+   * decode a value, waiting for more chunks when pending bytes run out.
+   * A value spanning chunks is decoded again from its start, once the bytes its failed
+   * read needed are buffered, so a long string is not retried on every chunk
+   */
+  async decodeAs<Data = any>(type: Type<Data>): Promise<Data> {
+    for (;;) {
+      try {
+        const data = this.decoder
+          .load(this.pending.subarray(this.offset, this.filled))
+          .decodeAs(type);
+        this.offset += this.decoder.cursor - this.decoder.begin;
+        return data;
+      } catch (e) {
+        if (!(e instanceof BincodeDecodeError && e.code === "UnexpectedEnd")) {
+          throw e;
+        }
+        const needed = e.needed ?? this.filled - this.offset + 1;
+        let pulled = false;
+        while (!pulled || this.filled - this.offset < needed) {
+          if (!(await this.pull())) {
+            if (!pulled) {
+              throw e;
+            }
+            break;
+          }
+          pulled = true;
+        }
+      }
+    }
+  }
+
+  /**
+   * decode a top-level `Vec<T>`, yielding each element once decoded
+   */
+  async *decodeVec<Data = any>(T: Type<Data>): AsyncGenerator<Data> {
+    try {
+      const vecLength = await this.decodeAs(length);
+      for (let idx = 0; idx < vecLength; idx += 1) {
+        yield await this.decodeAs(T);
+      }
+    } finally {
+      this.release();
+    }
+  }
+
+  /**
+   * stop listening to the signal and release the reader, an aborted reader is released
+   * once its stream is canceled
+   */
+  release() {
+    this.signal?.removeEventListener("abort", this.abort);
+    if (!this.signal?.aborted) {
+      this.reader.releaseLock();
+    }
+  }
+}
+
+/**
+ * decode a top-level `Vec<T>` from a stream, yielding each element once decoded
+ * @param options a config, or the config and an `AbortSignal` to cancel decoding
+ */
+export function decodeVecStream<Data = any>(
+  stream: ReadableStream<Uint8Array>,
+  T: Type<Data>,
+  options?: Config | { config?: Config; signal?: AbortSignal }
+): AsyncGenerator<Data> {
+  const { config, signal }: { config?: Config; signal?: AbortSignal } =
+    options instanceof Config ? { config: options } : options ?? {};
+  return new StreamDecoder(stream, config, signal).decodeVec(T);
+}
+
+export class StreamEncoder {
+  writer: WritableStreamDefaultWriter<Uint8Array>;
+  encoder: Encoder;
+  chunkSize: number;
+  /**
+   * @param chunkSize encoded bytes are written to the stream once they reach this size
+   */
+  constructor(
+    stream: WritableStream<Uint8Array>,
+    config?: Config,
+    chunkSize: number = 1 << 16
+  ) {
+    this.writer = stream.getWriter();
+    this.encoder = new Encoder(config).init(chunkSize);
+    this.chunkSize = chunkSize;
+  }
+
+  /**
+   * encode a value, writing pending bytes once they reach `chunkSize`,
+   * waits for `writer.ready` so a slow stream applies backpressure
+   */
+  async encodeAs<Data = any>(data: Data, type: Type<Data>): Promise<void> {
+    type.encode(data, this.encoder);
+    if (this.encoder.cursor >= this.chunkSize) {
+      await this.flush();
+    }
+  }
+
+  /**
+   * write pending bytes to the stream
+   */
+  async flush(): Promise<void> {
+    if (this.encoder.cursor === 0) {
+      return;
+    }
+    const chunk = this.encoder.buffer.slice(0, this.encoder.cursor);
+    this.encoder.init(this.chunkSize);
+    await this.writer.ready;
+    await this.writer.write(chunk);
+  }
+
+  /**
+   * flush pending bytes and close the stream
+   */
+  async close(): Promise<void> {
+    await this.flush();
+    await this.writer.close();
+  }
+
+  /**
+   * encode a top-level `Vec<T>`, the length is written first, so it must be known up front
+   * @param items elements, can be produced lazily
+   * @param vecLength number of elements
+   */
+  async encodeVec<Data = any>(
+    items: Iterable<Data> | AsyncIterable<Data>,
+    vecLength: number,
+    T: Type<Data>
+  ): Promise<void> {
+    await this.encodeAs(vecLength, length);
+    let count = 0;
+    for await (const item of items) {
+      if (count === vecLength) {
+        throw new Error(`expect ${vecLength} elements, found more`);
+      }
+      await this.encodeAs(item, T);
+      count += 1;
+    }
+    if (count !== vecLength) {
+      throw new Error(`expect ${vecLength} elements, found ${count}`);
+    }
+  }
+}
+
+/**
+ * encode a top-level `Vec<T>` to a stream, then close it
+ * @param vecLength number of elements, default to the length of an array
+ */
+export async function encodeVecStream<Data = any>(
+  stream: WritableStream<Uint8Array>,
+  items: Iterable<Data> | AsyncIterable<Data>,
+  T: Type<Data>,
+  options: { vecLength?: number; config?: Config; chunkSize?: number } = {}
+): Promise<void> {
+  const { config, chunkSize } = options;
+  const vecLength = options.vecLength ?? (items as Array<Data>).length;
+  if (vecLength === undefined) {
+    throw new Error("vecLength is required for iterables without a length");
+  }
+  const streamEncoder = new StreamEncoder(stream, config, chunkSize);
+  await streamEncoder.encodeVec(items, vecLength, T);
+  await streamEncoder.close();
+}
+
+/**
+ * decode a top-level `Vec<T>`, waiting for a macrotask every `yieldEvery` elements,
+ * to keep the thread responsive
+ */
+export async function decodeVecAsync<Data = any>(
+  buffer: ArrayBuffer | ArrayBufferView,
+  T: Type<Data>,
+  options: { yieldEvery?: number; config?: Config } = {}
+): Promise<Array<Data>> {
+  const { yieldEvery = 1000, config } = options;
+  const decoder = new Decoder(config).load(buffer);
+  const vecLength = decoder.decodeAs(length);
+  const result =
+    vecLength <= decoder.remaining() ? new Array<Data>(vecLength) : [];
+  for (let idx = 0; idx < vecLength; idx += 1) {
+    if (idx > 0 && idx % yieldEvery === 0) {
+      await new Promise((resolve) => setTimeout(resolve, 0));
+    }
+    result[idx] = T.decode(decoder);
+  }
+  return result;
+}
@@ -0,0 +1,73 @@
+import { Str, Type } from "./rust-type";
+import { Decoder } from "./decode";
+import { Encoder } from "./encode";
+
+/**
+ * `uuid::Uuid`, decoded as a hyphenated string like `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+ *
+ * bincode is not human readable, so `Uuid` serializes with `serialize_bytes`,
+ * a length then 16 bytes. Use `UuidCompact` for `#[serde(with = "uuid::serde::compact")]`,
+ * which writes 16 bytes without length, and `UuidString` for uuids serialized as strings.
+ */
+
+function formatUuid(bytes: Uint8Array): string {
+  const hex = Array.from(bytes, (byte) => byte.toString(16).padStart(2, "0"));
+  return [
+    hex.slice(0, 4),
+    hex.slice(4, 6),
+    hex.slice(6, 8),
+    hex.slice(8, 10),
+    hex.slice(10, 16),
+  ]
+    .map((group) => group.join(""))
+    .join("-");
+}
+
+function parseUuid(text: string): Uint8Array {
+  const hex = text.replace(/-/g, "");
+  if (!/^[0-9a-fA-F]{32}$/.test(hex)) {
+    throw new Error(`invalid uuid \`${text}\``);
+  }
+  const bytes = new Uint8Array(16);
+  for (let idx = 0; idx < 16; idx += 1) {
+    bytes[idx] = parseInt(hex.slice(idx * 2, idx * 2 + 2), 16);
+  }
+  return bytes;
+}
+
+export const UuidCompact: Type<string> = {
+  decode: (decoder: Decoder): string => {
+    return formatUuid(decoder.readBytes(16));
+  },
+  encode: (data: string, encoder: Encoder): void => {
+    encoder.writeBytes(parseUuid(data));
+  },
+};
+
+export const Uuid: Type<string> = {
+  decode: (decoder: Decoder): string => {
+    const offset = decoder.cursor;
+    const length = decoder.readLength();
+    if (length !== 16) {
+      throw decoder.error(
+        `expect 16 bytes for uuid, found ${length}`,
+        "InvalidValue",
+        offset
+      );
+    }
+    return UuidCompact.decode(decoder);
+  },
+  encode: (data: string, encoder: Encoder): void => {
+    encoder.writeLength(16);
+    UuidCompact.encode(data, encoder);
+  },
+};
+
+export const UuidString: Type<string> = {
+  decode: (decoder: Decoder): string => {
+    return Str.decode(decoder);
+  },
+  encode: (data: string, encoder: Encoder): void => {
+    Str.encode(data, encoder);
+  },
+};
@@ -0,0 +1,93 @@
+import { Decoder, Encoder } from "../src";
+import {
+  InlineStruct,
+  Str,
+  Struct,
+  Tuple,
+  Type,
+  TypeEntries,
+  f32,
+  u32,
+  Vec,
+} from "../src/rust-type";
+
+// run manually, compares the shared text decoder against per-call allocation
+const strings = Array.from(
+  { length: 10000 },
+  (_, idx) => `string ${idx} 字符串`
+);
+const bincode = new Encoder().init().encodeAs(strings, Vec(Str));
+
+const PerCallStr: Type<string> = {
+  decode: (decoder: Decoder): string => {
+    const length = decoder.readLength();
+    return new TextDecoder().decode(decoder.readBytes(length));
+  },
+  encode: Str.encode,
+};
+
+function bench<Data>(
+  name: string,
+  type: Type<Data>,
+  input: Uint8Array = bincode,
+  rounds: number = 20
+) {
+  const decoder = new Decoder();
+  const start = performance.now();
+  for (let round = 0; round < rounds; round += 1) {
+    decoder.load(input.buffer).decodeAs(type);
+  }
+  console.log(`${name}: ${((performance.now() - start) / rounds).toFixed(2)}ms`);
+}
+
+bench("per-call TextDecoder", Vec(PerCallStr));
+bench("shared TextDecoder", Vec(Str));
+
+// compares Struct against InlineStruct on a 1M-element vec
+type Person = {
+  name: string;
+  age: number;
+};
+const fields: TypeEntries<Person> = [
+  ["name", Str],
+  ["age", u32],
+];
+const people = Array.from({ length: 1000000 }, (_, idx) => ({
+  name: `person ${idx}`,
+  age: idx % 100,
+}));
+const peopleBincode = new Encoder()
+  .init()
+  .encodeAs(people, Vec(Struct<Person>(fields)));
+bench("Vec<Struct>", Vec(Struct<Person>(fields)), peopleBincode, 3);
+bench("Vec<InlineStruct>", Vec(InlineStruct<Person>(fields)), peopleBincode, 3);
+
+// compares allocating decode against decodeInto on a per-frame game state
+type Entity = { id: number; position: [number, number]; health: number };
+const Entity = Struct<Entity>([
+  ["id", u32],
+  ["position", Tuple<[number, number]>([f32, f32])],
+  ["health", f32],
+]);
+const GameState = Vec(Entity);
+const gameStateBincode = new Encoder().init().encodeAs(
+  Array.from({ length: 1000 }, (_, idx) => ({
+    id: idx,
+    position: [idx, -idx] as [number, number],
+    health: 100,
+  })),
+  GameState
+);
+const frames = 10000;
+const frameDecoder = new Decoder();
+let start = performance.now();
+for (let frame = 0; frame < frames; frame += 1) {
+  frameDecoder.load(gameStateBincode).decodeAs(GameState);
+}
+console.log(`decodeAs: ${((performance.now() - start) / frames).toFixed(3)}ms`);
+const gameState: Array<Entity> = [];
+start = performance.now();
+for (let frame = 0; frame < frames; frame += 1) {
+  frameDecoder.load(gameStateBincode).decodeInto(GameState, gameState);
+}
+console.log(`decodeInto: ${((performance.now() - start) / frames).toFixed(3)}ms`);
@@ -0,0 +1,64 @@
+import { strict as assert } from "node:assert";
+import { Decoder, Encoder } from "../src";
+import {
+  base64ToBytes,
+  Base64Bytes,
+  bytesToBase64,
+  bytesToHex,
+  HexBytes,
+  hexToBytes,
+} from "../src/bytes";
+import { Bytes, Struct } from "../src/rust-type";
+
+const decoder = new Decoder();
+const encoder = new Encoder();
+
+// helpers
+const sample = new Uint8Array([0, 1, 0x7f, 0x80, 0xfe, 0xff]);
+assert(
+  bytesToHex(sample) === "00017f80feff" &&
+    hexToBytes("00017F80FEFF").join() === sample.join(),
+  "hex should round trip"
+);
+for (const [bytes, base64] of [
+  [sample, "AAF/gP7/"],
+  [sample.subarray(0, 5), "AAF/gP4="],
+  [sample.subarray(0, 4), "AAF/gA=="],
+  [new Uint8Array(0), ""],
+] as Array<[Uint8Array, string]>) {
+  assert(
+    bytesToBase64(bytes) === base64 &&
+      base64ToBytes(base64).join() === bytes.join(),
+    "base64 should round trip with padding"
+  );
+}
+assert.throws(
+  () => hexToBytes("abc"),
+  /invalid hex `abc`/,
+  "odd length hex should be rejected"
+);
+
+// byte fields as text
+type Blob = { digest: string; payload: string };
+const Blob = Struct<Blob>([
+  ["digest", HexBytes],
+  ["payload", Base64Bytes],
+]);
+const blob = { digest: "deadbeef", payload: "aGk=" };
+const blobBincode = encoder.init().encodeAs(blob, Blob);
+assert(
+  blobBincode.join() ===
+    encoder
+      .init()
+      .encodeAs(
+        { digest: hexToBytes("deadbeef"), payload: base64ToBytes("aGk=") },
+        Struct<{ digest: Uint8Array; payload: Uint8Array }>([
+          ["digest", Bytes],
+          ["payload", Bytes],
+        ])
+      )
+      .join() &&
+    JSON.stringify(decoder.load(blobBincode).decodeAs(Blob)) ===
+      JSON.stringify(blob),
+  "byte fields should round trip through hex and base64"
+);
@@ -0,0 +1,31 @@
+import { strict as assert } from "node:assert";
+import { Decoder, Encoder } from "../src";
+import { DateTimeUtc, NaiveDateTime } from "../src/chrono";
+import { Str } from "../src/rust-type";
+
+const decoder = new Decoder();
+const encoder = new Encoder();
+
+// serde writes chrono datetimes as strings
+const utcBincode = encoder
+  .init()
+  .encodeAs("2023-01-01T08:00:00.123456789Z", Str);
+assert(
+  decoder.load(utcBincode.buffer).decodeAs(DateTimeUtc).getTime() ===
+    1672560000123,
+  "DateTime<Utc> should decode a known timestamp"
+);
+const naiveBincode = encoder.init().encodeAs("2023-01-01T08:00:00", Str);
+assert(
+  decoder.load(naiveBincode.buffer).decodeAs(NaiveDateTime).getTime() ===
+    1672560000000,
+  "NaiveDateTime should decode as UTC"
+);
+const date = new Date(1672560000123);
+assert(
+  decoder
+    .load(encoder.init().encodeAs(date, DateTimeUtc).buffer)
+    .decodeAs(DateTimeUtc)
+    .getTime() === date.getTime(),
+  "DateTime<Utc> should round trip"
+);
@@ -0,0 +1,1369 @@
+import { strict as assert } from "node:assert";
+import {
+  BincodeDecodeError,
+  Config,
+  Decoder,
+  Encoder,
+  LazyString,
+  StringBytes,
+} from "../src";
+import { enumData, Variant } from "../src/enum-data";
+import {
+  Arc,
+  Arr,
+  AutoMap,
+  BinaryHeap,
+  bool,
+  Box,
+  Brand,
+  Branded,
+  BTreeMap,
+  ByteArr,
+  Bytes,
+  char,
+  Class,
+  ConsList,
+  Cow,
+  empty,
+  Enum,
+  f32,
+  f64,
+  HashMap,
+  i8,
+  InlineStruct,
+  InternallyTagged,
+  IpAddr,
+  Lazy,
+  LazyStr,
+  Mutex,
+  None,
+  Nullable,
+  ObjectMap,
+  Option,
+  Optional,
+  OptionalProp,
+  PhantomData,
+  Range,
+  RangeInclusive,
+  Readonly,
+  RwLock,
+  Schema,
+  SchemaChecked,
+  schemaHash,
+  Skip,
+  SocketAddr,
+  Some,
+  Str,
+  StrBytes,
+  Struct,
+  Tuple,
+  Type,
+  TypedArr,
+  TypedVec,
+  TypeEntries,
+  u16,
+  u32,
+  u64,
+  u8,
+  Untagged,
+  Validated,
+  Vec,
+} from "../src/rust-type";
+
+const decoder = new Decoder();
+const encoder = new Encoder();
+
+// PhantomData
+type Tagged = {
+  id: number;
+  marker: undefined;
+};
+const Tagged = <T>(): Type<Tagged> =>
+  Struct<Tagged>([
+    ["id", u32],
+    ["marker", PhantomData<T>()],
+  ]);
+type A = { a: number };
+type B = { b: string };
+assert(
+  PhantomData<A>() === PhantomData<B>(),
+  "PhantomData should share one type defination"
+);
+const taggedA = encoder.init().encodeAs({ id: 7, marker: undefined }, Tagged<A>());
+const taggedB = encoder.init().encodeAs({ id: 7, marker: undefined }, Tagged<B>());
+assert(
+  taggedA.join() === taggedB.join() && taggedA.length === 4,
+  "PhantomData should not change the wire shape"
+);
+assert.deepStrictEqual(
+  decoder.load(taggedA.buffer).decodeAs(Tagged<B>()),
+  { id: 7, marker: undefined },
+  "PhantomData should decode as undefined"
+);
+
+// generic struct
+type Paginated<T> = {
+  items: Array<T>;
+  next: Variant<0> | Variant<1, string>;
+};
+const Paginated = <T>(T: Type<T>) =>
+  Struct<Paginated<T>>([
+    ["items", Vec(T)],
+    ["next", Option(Str)],
+  ]);
+type Person = {
+  name: string;
+  age: number;
+};
+const Person = Struct<Person>([
+  ["name", Str],
+  ["age", u32],
+]);
+const page: Paginated<Person> = {
+  items: [
+    { name: "alice", age: 30 },
+    { name: "bob", age: 25 },
+  ],
+  next: Some("cursor"),
+};
+const pageBincode = encoder.init().encodeAs(page, Paginated(Person));
+const decodedPage = decoder.load(pageBincode.buffer).decodeAs(Paginated(Person));
+assert(
+  decodedPage.items[1].name === "bob" && decodedPage.next.data === "cursor",
+  "generic struct should round trip"
+);
+
+// typed vec
+const floats = new Float32Array([1.5, -2.25, 3.125]);
+const F32Vec = TypedVec(Float32Array, f32);
+const alignedBincode = encoder.init().encodeAs(floats, F32Vec);
+const aligned = decoder.load(alignedBincode.buffer).decodeAs(F32Vec);
+assert(
+  aligned instanceof Float32Array && aligned.join() === floats.join(),
+  "aligned typed vec should round trip"
+);
+const Unaligned = Tuple<[number, Float32Array]>([u8, F32Vec]);
+const unalignedBincode = encoder.init().encodeAs([1, floats], Unaligned);
+const [, unaligned] = decoder.load(unalignedBincode.buffer).decodeAs(Unaligned);
+assert(
+  unaligned instanceof Float32Array && unaligned.join() === floats.join(),
+  "unaligned typed vec should round trip"
+);
+
+// typed vec fallbacks
+const U32Vec = TypedVec(Uint32Array, u32);
+const F64Vec = TypedVec(Float64Array, f64);
+const words = new Uint32Array([0, 250, 251, 0x01020304]);
+const doubles = new Float64Array([0.1, -0, Infinity]);
+for (const config of [
+  new Config(),
+  new Config().with_big_endian(),
+  new Config().with_varint_encoding(),
+]) {
+  const wordsBincode = new Encoder(config).init().encodeAs(words, U32Vec);
+  const doublesBincode = new Encoder(config).init().encodeAs(doubles, F64Vec);
+  assert(
+    wordsBincode.join() ===
+      new Encoder(config).init().encodeAs(Array.from(words), Vec(u32)).join() &&
+      doublesBincode.join() ===
+        new Encoder(config).init().encodeAs(Array.from(doubles), Vec(f64)).join(),
+    "typed vec should write the same bytes as Vec in any config"
+  );
+  assert(
+    new Decoder(config).load(wordsBincode).decodeAs(U32Vec).join() ===
+      words.join() &&
+      new Decoder(config)
+        .load(doublesBincode)
+        .decodeAs(F64Vec)
+        .every((value, idx) => Object.is(value, doubles[idx])),
+    "typed vec should decode in any config"
+  );
+}
+
+// i8 and u16 fast paths
+const signed = Int8Array.from({ length: 4096 }, (_, idx) => (idx % 256) - 128);
+const I8Vec = TypedVec(Int8Array, i8);
+const signedBincode = encoder.init().encodeAs(signed, I8Vec);
+const decodedSigned = decoder.load(signedBincode.buffer).decodeAs(I8Vec);
+assert(
+  signedBincode.join() ===
+    encoder.init().encodeAs(Array.from(signed), Vec(i8)).join() &&
+    decodedSigned instanceof Int8Array &&
+    decodedSigned.join() === signed.join(),
+  "Vec<i8> should decode as Int8Array"
+);
+const U16Arr = TypedArr(Uint16Array, u16, 3);
+const shorts = new Uint16Array([1, 0x0203, 0xffff]);
+for (const config of [
+  new Config().with_little_endian(),
+  new Config().with_big_endian(),
+]) {
+  const shortsBincode = new Encoder(config).init().encodeAs(shorts, U16Arr);
+  assert(
+    shortsBincode.join() ===
+      new Encoder(config).init().encodeAs(Array.from(shorts), Arr(u16, 3)).join() &&
+      new Decoder(config).load(shortsBincode.buffer).decodeAs(U16Arr).join() ===
+        shorts.join(),
+    "[u16; 3] should decode as Uint16Array in either endianness"
+  );
+}
+
+// byte arrays
+type KeyEntry = {
+  key: Uint8Array;
+  sealed: Variant<0> | Variant<1, Uint8Array>;
+};
+const KeyEntry = Struct<KeyEntry>([
+  ["key", ByteArr(32)],
+  ["sealed", Option(ByteArr(32))],
+]);
+const key = Uint8Array.from({ length: 32 }, (_, idx) => idx);
+const keyEntryBincode = encoder
+  .init()
+  .encodeAs({ key, sealed: Some(key.slice().reverse()) }, KeyEntry);
+const keyEntry = decoder.load(keyEntryBincode).decodeAs(KeyEntry);
+assert(
+  keyEntryBincode.length === 32 + 1 + 32 &&
+    keyEntry.key instanceof Uint8Array &&
+    keyEntry.key.join() === key.join() &&
+    keyEntry.sealed.data?.[0] === 31,
+  "[u8; 32] should decode as Uint8Array inside structs and enums"
+);
+
+// cow
+const CowFields = Tuple<
+  [Variant<0> | Variant<1, string>, Array<Uint8Array>]
+>([Option(Cow(Str)), Vec(Cow(Bytes))]);
+const cowBincode = encoder
+  .init()
+  .encodeAs([Some("borrowed"), [new Uint8Array([1, 2]), new Uint8Array([3])]], CowFields);
+const [cowStr, cowBytes] = decoder.load(cowBincode.buffer).decodeAs(CowFields);
+assert(
+  cowStr.data === "borrowed" &&
+    cowBytes[0] instanceof Uint8Array &&
+    cowBytes[1].join() === "3",
+  "cow should decode as the owned type"
+);
+
+// option of bytes
+const OptionBytes = Option(Bytes);
+const someBytes = decoder
+  .load(encoder.init().encodeAs(Some(new Uint8Array([1, 2, 3])), OptionBytes).buffer)
+  .decodeAs(OptionBytes);
+assert(
+  someBytes.data instanceof Uint8Array && someBytes.data.join() === "1,2,3",
+  "Some(Vec<u8>) should decode as Uint8Array"
+);
+const noneBytes = decoder
+  .load(encoder.init().encodeAs(None(), OptionBytes).buffer)
+  .decodeAs(OptionBytes);
+assert(
+  noneBytes.variant === 0 && noneBytes.data === undefined,
+  "None should decode without data"
+);
+
+// recursive tree
+type TreeNode = {
+  value: number;
+  left: Variant<0> | Variant<1, TreeNode>;
+  right: Variant<0> | Variant<1, TreeNode>;
+};
+const TreeNode: Type<TreeNode> = Struct<TreeNode>([
+  ["value", u32],
+  ["left", Option(Box(Lazy(() => TreeNode)))],
+  ["right", Option(Box(Lazy(() => TreeNode)))],
+]);
+const tree: TreeNode = {
+  value: 1,
+  left: Some({ value: 2, left: None(), right: None() }),
+  right: Some({
+    value: 3,
+    left: Some({ value: 4, left: None(), right: None() }),
+    right: None(),
+  }),
+};
+const treeBincode = encoder.init().encodeAs(tree, TreeNode);
+const decodedTree = decoder.load(treeBincode.buffer).decodeAs(TreeNode);
+assert(
+  JSON.stringify(decodedTree) === JSON.stringify(tree),
+  "recursive tree should round trip"
+);
+
+// nullable and optional
+const NullableU32 = Nullable(u32);
+const OptionalU32 = Optional(u32);
+const noneBincode = encoder.init().encodeAs(null, NullableU32);
+assert(noneBincode.length === 1, "None should take one tag byte");
+assert(
+  decoder.load(noneBincode.buffer).decodeAs(NullableU32) === null,
+  "Nullable should decode None as null"
+);
+assert(
+  decoder.load(noneBincode.buffer).decodeAs(OptionalU32) === undefined,
+  "Optional should decode None as undefined"
+);
+const someBincode = encoder.init().encodeAs(5, OptionalU32);
+assert(
+  decoder.load(someBincode.buffer).decodeAs(NullableU32) === 5,
+  "Nullable should decode Some as the value"
+);
+
+type Profile = {
+  name: string;
+  age?: number;
+  email?: string;
+  nickname: string | null;
+};
+const Profile = Struct<Profile>([
+  ["name", Str],
+  ["age", OptionalProp(u32)],
+  ["email", OptionalProp(Str)],
+  ["nickname", Nullable(Str)],
+]);
+const profileBincode = encoder
+  .init()
+  .encodeAs({ name: "alice", email: "a@b.c", nickname: null }, Profile);
+const profile = decoder.load(profileBincode.buffer).decodeAs(Profile);
+assert(
+  Object.keys(profile).join() === "name,email,nickname" &&
+    profile.email === "a@b.c" &&
+    profile.nickname === null,
+  "OptionalProp None fields should be absent, Nullable fields null"
+);
+assert(
+  encoder.init().encodeAs(JSON.parse(JSON.stringify(profile)), Profile).join() ===
+    profileBincode.join(),
+  "optional fields should survive a json round trip"
+);
+
+// recursive json-like value
+namespace Value {
+  export enum _ {
+    Null,
+    Bool,
+    Num,
+    Str,
+    Arr,
+    Obj,
+  }
+  export type $ =
+    | Variant<_.Null>
+    | Variant<_.Bool, boolean>
+    | Variant<_.Num, number>
+    | Variant<_.Str, string>
+    | Variant<_.Arr, Array<$>>
+    | Variant<_.Obj, Map<string, $>>;
+  export const Type: Type<$> = Enum<$>({
+    [_.Null]: empty,
+    [_.Bool]: bool,
+    [_.Num]: f64,
+    [_.Str]: Str,
+    [_.Arr]: Vec(Lazy(() => Type)),
+    [_.Obj]: HashMap(Str, Lazy(() => Type)),
+  });
+}
+const json = enumData<Value.$>(
+  Value._.Obj,
+  new Map<string, Value.$>([
+    ["ok", enumData<Value.$>(Value._.Bool, true)],
+    [
+      "list",
+      enumData<Value.$>(Value._.Arr, [
+        enumData<Value.$>(Value._.Num, 1.5),
+        enumData<Value.$>(Value._.Null, undefined),
+        enumData<Value.$>(Value._.Str, "nested"),
+      ]),
+    ],
+  ])
+);
+const jsonBincode = encoder.init().encodeAs(json, Value.Type);
+const decodedJson = decoder.load(jsonBincode.buffer).decodeAs(Value.Type);
+const decodedList = (decodedJson.data as Map<string, Value.$>).get("list");
+assert(
+  decodedList !== undefined &&
+    (decodedList.data as Array<Value.$>)[2].data === "nested",
+  "recursive value should round trip"
+);
+decoder.config.with_recursion_limit(1);
+assert.throws(
+  () => decoder.load(jsonBincode.buffer).decodeAs(Value.Type),
+  (e) => e instanceof BincodeDecodeError && e.code === "RecursionLimit",
+  "recursion limit should be enforced"
+);
+decoder.config.with_recursion_limit(128);
+
+// clamped bytes
+const Pixels = TypedVec(Uint8ClampedArray, u8);
+const pixelsBincode = encoder
+  .init()
+  .encodeAs(new Uint8Array([0, 128, 255]), Bytes);
+const pixels = decoder.load(pixelsBincode.buffer).decodeAs(Pixels);
+assert(
+  pixels instanceof Uint8ClampedArray && pixels.join() === "0,128,255",
+  "pixels should decode as Uint8ClampedArray"
+);
+
+// net
+for (const ip of ["127.0.0.1", "::1", "2001:db8::1:0:0:1", "::ffff:10.0.0.1"]) {
+  const ipBincode = encoder.init().encodeAs(ip, IpAddr);
+  assert(
+    decoder.load(ipBincode.buffer).decodeAs(IpAddr) === ip,
+    `IpAddr ${ip} should round trip`
+  );
+}
+const v4Socket = encoder.init().encodeAs("127.0.0.1:8080", SocketAddr);
+assert(
+  v4Socket.join() === "0,0,0,0,127,0,0,1,144,31",
+  "SocketAddr V4 should match bincode layout"
+);
+const v6Socket = encoder.init().encodeAs("[::1]:8080", SocketAddr);
+assert(
+  v6Socket[0] === 1 && v6Socket.length === 4 + 16 + 2,
+  "SocketAddr V6 should match bincode layout"
+);
+assert(
+  decoder.load(v6Socket.buffer).decodeAs(SocketAddr) === "[::1]:8080",
+  "SocketAddr V6 should round trip"
+);
+for (const NetType of [IpAddr, SocketAddr]) {
+  assert.throws(
+    () => decoder.load(new Uint8Array([2, 0, 0, 0]).buffer).decodeAs(NetType),
+    (e) =>
+      e instanceof BincodeDecodeError &&
+        e.code === "UnknownVariant" &&
+        e.offset === 0,
+    "unknown net address variant should be an unknown variant"
+  );
+}
+
+// readonly
+const ReadonlyPage = Readonly(Paginated(Person));
+const readonlyPage = decoder.load(pageBincode.buffer).decodeAs(ReadonlyPage);
+assert(readonlyPage.items.length === 2, "readonly should decode the same");
+function mutateReadonlyPage() {
+  // @ts-expect-error items is readonly
+  readonlyPage.items = [];
+  // @ts-expect-error items is a readonly array
+  readonlyPage.items.push({ name: "carol", age: 20 });
+  // @ts-expect-error elements are readonly
+  readonlyPage.items[0].age = 1;
+}
+
+// sorted multimap
+const MultiMap = BTreeMap(Str, Bytes);
+const multiMap = new Map([
+  ["a", new Uint8Array([1])],
+  ["b", new Uint8Array([2, 3])],
+  ["c", new Uint8Array([])],
+]);
+const multiMapBincode = encoder.init().encodeAs(multiMap, MultiMap);
+const decodedMultiMap = decoder.load(multiMapBincode.buffer).decodeAs(MultiMap);
+assert(
+  [...decodedMultiMap.keys()].join() === "a,b,c" &&
+    [...decodedMultiMap.values()].every((value) => value instanceof Uint8Array) &&
+    decodedMultiMap.get("b")?.join() === "2,3",
+  "sorted multimap should keep order and decode byte vectors"
+);
+
+// binary heap
+// `BinaryHeap::from(vec![1, 5, 3, 4])` of rust, written in heap order
+const heapBincode = new Uint8Array([
+  4, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 4, 0, 0, 0, 3, 0, 0, 0, 1, 0, 0, 0,
+]);
+const heap = decoder.load(heapBincode).decodeAs(BinaryHeap(u32));
+assert(
+  heap.join() === "5,4,3,1" &&
+    encoder.init().encodeAs(heap, BinaryHeap(u32)).join() === heapBincode.join(),
+  "BinaryHeap<u32> should keep the heap order"
+);
+
+// non-string keys
+const Blobs = HashMap(u32, Str);
+const blobs = new Map([
+  [7, "seven"],
+  [70000, "big"],
+]);
+const blobsBincode = encoder.init().encodeAs(blobs, Blobs);
+const decodedBlobs = decoder.load(blobsBincode.buffer).decodeAs(Blobs);
+assert(
+  blobsBincode.length === 8 + (4 + 8 + 5) + (4 + 8 + 3) &&
+    decodedBlobs.get(70000) === "big" &&
+    [...decodedBlobs.keys()].join() === "7,70000",
+  "u32 keyed map should decode into Map in wire order"
+);
+const Grid = HashMap(Tuple<[number, number]>([u8, u8]), bool);
+const gridBincode = encoder.init().encodeAs(
+  new Map([
+    [[0, 1], true],
+    [[2, 3], false],
+  ]),
+  Grid
+);
+const decodedGrid = [...decoder.load(gridBincode.buffer).decodeAs(Grid)];
+assert(
+  gridBincode.length === 8 + 3 * 2 &&
+    JSON.stringify(decodedGrid) === "[[[0,1],true],[[2,3],false]]",
+  "tuple keyed map should decode keys with the tuple type"
+);
+const Scores = ObjectMap(u32);
+const scoresBincode = encoder.init().encodeAs({ alice: 99, bob: 1 }, Scores);
+assert(
+  scoresBincode.join() ===
+    encoder
+      .init()
+      .encodeAs(
+        new Map([
+          ["alice", 99],
+          ["bob", 1],
+        ]),
+        HashMap(Str, u32)
+      )
+      .join() &&
+    JSON.stringify(decoder.load(scoresBincode.buffer).decodeAs(Scores)) ===
+      '{"alice":99,"bob":1}',
+  "ObjectMap should share the HashMap layout"
+);
+
+// schema hash
+const PersonChecked = SchemaChecked(0x5eed1234n, Person);
+const checkedBincode = encoder
+  .init()
+  .encodeAs({ name: "alice", age: 30 }, PersonChecked);
+assert(
+  decoder.load(checkedBincode.buffer).decodeAs(PersonChecked).name === "alice",
+  "matching schema hash should decode"
+);
+assert.throws(
+  () =>
+    decoder
+      .load(checkedBincode.buffer)
+      .decodeAs(SchemaChecked(0x5eed1235n, Person)),
+  (e) => e instanceof BincodeDecodeError && e.code === "SchemaMismatch",
+  "mismatching schema hash should throw"
+);
+
+// runtime schema
+// a stand-in for `z.object({ name: z.string().min(1), age: z.number().int() })`
+const PersonSchema: Schema<Person> = {
+  parse(data: any): Person {
+    if (typeof data?.name !== "string" || data.name.length === 0) {
+      throw new Error("name must be a non-empty string");
+    }
+    if (!Number.isInteger(data.age)) {
+      throw new Error("age must be an integer");
+    }
+    return data;
+  },
+};
+const ValidPerson = Validated(PersonSchema, Person);
+assert(
+  decoder
+    .load(encoder.init().encodeAs({ name: "alice", age: 30 }, Person))
+    .decodeAs(ValidPerson).name === "alice",
+  "valid person should pass the schema"
+);
+assert.throws(
+  () => {
+    decoder
+      .load(encoder.init().encodeAs({ name: "", age: 30 }, Person))
+      .decodeAs(ValidPerson);
+  },
+  (e) =>
+    e instanceof BincodeDecodeError &&
+      e.code === "InvalidValue" &&
+      e.message.includes("name must be a non-empty string"),
+  "schema failure should be an InvalidValue error"
+);
+
+// renamed fields
+type User = {
+  userName: string;
+  age: number;
+};
+const User = Struct<User>([
+  ["userName", Str],
+  ["age", u32],
+]);
+assert(
+  encoder.init().encodeAs({ userName: "alice", age: 30 }, User).join() ===
+    encoder.init().encodeAs({ name: "alice", age: 30 }, Person).join(),
+  "renamed fields should keep positional layout"
+);
+
+// discriminant and variant name
+assert(
+  Value._[decodedJson.variant] === "Obj" && Value._["Obj"] === 5,
+  "variants code enum should map discriminant and name"
+);
+
+// struct variant with nested collections
+namespace Message {
+  export enum _ {
+    Text,
+    Data,
+  }
+  export type Data = {
+    content: string;
+    people: Array<Person>;
+    scores: Map<string, number>;
+    size: number;
+  };
+  export type $ = Variant<_.Text, string> | Variant<_.Data, Data>;
+  export const Type: Type<$> = Enum<$>({
+    [_.Text]: Str,
+    [_.Data]: Struct<Data>([
+      ["content", Str],
+      ["people", Vec(Person)],
+      ["scores", HashMap(Str, u32)],
+      ["size", u32],
+    ]),
+  });
+}
+const dataMessage = enumData<Message.$>(Message._.Data, {
+  content: "payload",
+  people: [{ name: "alice", age: 30 }],
+  scores: new Map([["alice", 99]]),
+  size: 7,
+});
+const messageBincode = encoder.init().encodeAs(dataMessage, Message.Type);
+const decodedMessage = decoder.load(messageBincode.buffer).decodeAs(Message.Type);
+assert(
+  decodedMessage.variant === Message._.Data &&
+    (decodedMessage.data as Message.Data).people[0].name === "alice" &&
+    (decodedMessage.data as Message.Data).scores.get("alice") === 99 &&
+    (decodedMessage.data as Message.Data).size === 7,
+  "struct variant should decode fields in declaration order"
+);
+
+// lazy string
+const LazyMessage = Tuple<[LazyString, number]>([LazyStr, u32]);
+const lazyBincode = encoder
+  .init()
+  .encodeAs(["早上好", 1], Tuple<[string, number]>([Str, u32]));
+const [lazyText, lazyNumber] = decoder
+  .load(lazyBincode.buffer)
+  .decodeAs(LazyMessage);
+assert(
+  lazyText instanceof LazyString &&
+    lazyText.bytes.length === 9 &&
+    lazyText.toString() === "早上好" &&
+    lazyNumber === 1,
+  "lazy string should decode on demand"
+);
+assert(
+  encoder.init().encodeAs([lazyText, 1], LazyMessage).join() ===
+    lazyBincode.join(),
+  "lazy string should encode its bytes"
+);
+
+// string bytes
+const ForwardedMessage = Tuple<[StringBytes, number]>([StrBytes, u32]);
+const forwardBuffer = lazyBincode.slice();
+const [forwardedText] = decoder.load(forwardBuffer).decodeAs(ForwardedMessage);
+forwardBuffer.fill(0);
+assert(
+  forwardedText instanceof StringBytes &&
+    forwardedText.bytes.length === 9 &&
+    forwardedText.decode() === "早上好",
+  "string bytes should be copied and decoded by decode()"
+);
+assert(
+  encoder.init().encodeAs([forwardedText, 1], ForwardedMessage).join() ===
+    lazyBincode.join(),
+  "string bytes should encode as a string"
+);
+
+// zero-sized fields between real fields
+type WithMarker = {
+  before: number;
+  marker: undefined;
+  unit: undefined;
+  after: string;
+};
+const WithMarker = Struct<WithMarker>([
+  ["before", u32],
+  ["marker", PhantomData<A>()],
+  ["unit", empty],
+  ["after", Str],
+]);
+type WithoutMarker = {
+  before: number;
+  after: string;
+};
+const WithoutMarker = Struct<WithoutMarker>([
+  ["before", u32],
+  ["after", Str],
+]);
+const markerBincode = encoder
+  .init()
+  .encodeAs(
+    { before: 1, marker: undefined, unit: undefined, after: "x" },
+    WithMarker
+  );
+const withoutMarker = decoder.load(markerBincode.buffer).decodeAs(WithoutMarker);
+assert(
+  withoutMarker.before === 1 && withoutMarker.after === "x",
+  "zero-sized fields should not change decode order"
+);
+
+// nested tuples
+const FiveTuple = Tuple<[number, string, boolean, bigint, number]>([
+  u8,
+  Str,
+  bool,
+  u64,
+  f64,
+]);
+const fiveTuple: [number, string, boolean, bigint, number] = [
+  1,
+  "two",
+  true,
+  4n,
+  5.5,
+];
+const fiveBincode = encoder.init().encodeAs(fiveTuple, FiveTuple);
+assert(
+  decoder.load(fiveBincode.buffer).decodeAs(FiveTuple).join() ===
+    fiveTuple.join(),
+  "5-tuple should round trip"
+);
+type Nested = [[number, number], Array<[string, number]>];
+const Nested = Tuple<Nested>([
+  Tuple<[number, number]>([u8, u8]),
+  Vec(Tuple<[string, number]>([Str, f64])),
+]);
+const nested: Nested = [
+  [1, 2],
+  [
+    ["a", 0.5],
+    ["b", 1.5],
+  ],
+];
+const nestedBincode = encoder.init().encodeAs(nested, Nested);
+assert(
+  JSON.stringify(decoder.load(nestedBincode.buffer).decodeAs(Nested)) ===
+    JSON.stringify(nested),
+  "tuple nested in vec should round trip"
+);
+
+// options and vecs inside tuples
+const TrailingOption = Tuple<[number, string | null]>([u32, Nullable(Str)]);
+const trailingSome = encoder.init().encodeAs([7, "x"], TrailingOption);
+const trailingNone = encoder.init().encodeAs([7, null], TrailingOption);
+assert(
+  trailingSome.join() === "7,0,0,0,1,1,0,0,0,0,0,0,0,120" &&
+    trailingNone.join() === "7,0,0,0,0" &&
+    decoder.load(trailingSome).decodeAs(TrailingOption).join() === "7,x" &&
+    decoder.load(trailingNone).decodeAs(TrailingOption)[1] === null,
+  "(u32, Option<String>) should decode the trailing option"
+);
+const LeadingOption = Tuple<[number | null, number]>([Nullable(u8), u8]);
+const leadingSome = encoder.init().encodeAs([1, 2], LeadingOption);
+const leadingNone = encoder.init().encodeAs([null, 2], LeadingOption);
+assert(
+  leadingSome.join() === "1,1,2" &&
+    leadingNone.join() === "0,2" &&
+    decoder.load(leadingSome).decodeAs(LeadingOption).join() === "1,2" &&
+    decoder.load(leadingNone).decodeAs(LeadingOption).join() === ",2",
+  "(Option<u8>, u8) should decode the leading option"
+);
+const LeadingVec = Tuple<[Array<number>, boolean]>([Vec(u32), bool]);
+const leadingVec = encoder.init().encodeAs([[1, 2], true], LeadingVec);
+assert(
+  leadingVec.length === 8 + 8 + 1 &&
+    JSON.stringify(decoder.load(leadingVec).decodeAs(LeadingVec)) ===
+      "[[1,2],true]",
+  "(Vec<u32>, bool) should decode the leading vec"
+);
+
+// range
+const ranges = Tuple<[Range<bigint>, Range<number>]>([
+  Range(u64),
+  RangeInclusive(u32),
+]);
+const rangesBincode = encoder.init().encodeAs(
+  [
+    { start: 1n, end: 10n },
+    { start: 3, end: 7 },
+  ],
+  ranges
+);
+assert(
+  rangesBincode.length === 16 + 8 &&
+    rangesBincode[0] === 1 &&
+    rangesBincode[8] === 10,
+  "range should write start then end"
+);
+const [range, rangeInclusive] = decoder
+  .load(rangesBincode.buffer)
+  .decodeAs(ranges);
+assert(
+  range.end === 10n && rangeInclusive.start === 3 && rangeInclusive.end === 7,
+  "range should round trip"
+);
+
+// serde_bytes field next to a regular Vec<u8>
+type Blob = {
+  raw: Uint8Array;
+  list: Array<number>;
+};
+const Blob = Struct<Blob>([
+  ["raw", Bytes],
+  ["list", Vec(u8)],
+]);
+const blobBincode = encoder
+  .init()
+  .encodeAs({ raw: new Uint8Array([1, 2]), list: [3, 4] }, Blob);
+const blob = decoder.load(blobBincode.buffer).decodeAs(Blob);
+assert(
+  blob.raw instanceof Uint8Array &&
+    Array.isArray(blob.list) &&
+    blob.raw.join() === "1,2" &&
+    blob.list.join() === "3,4",
+  "Bytes field should decode as Uint8Array, Vec(u8) as array"
+);
+
+// branded newtypes
+const UserId = Branded("UserId", u64);
+const PostId = Branded("PostId", u64);
+type UserId = Brand<bigint, "UserId">;
+type PostId = Brand<bigint, "PostId">;
+const userId: UserId = decoder
+  .load(encoder.init().encodeAs(42n, u64).buffer)
+  .decodeAs(UserId);
+assert(userId === 42n, "branded value should decode unchanged");
+function mixBrands(postId: PostId) {
+  // @ts-expect-error brands should not unify
+  const wrongId: UserId = postId;
+  encoder.init().encodeAs(postId, PostId);
+  // @ts-expect-error brands should not unify
+  encoder.init().encodeAs(postId, UserId);
+}
+
+// skipped field in the middle
+type Session = {
+  id: number;
+  cache: Map<string, number>;
+  retries: number;
+  token: string;
+};
+const Session = Struct<Session>([
+  ["id", u32],
+  ["cache", Skip(() => new Map<string, number>())],
+  ["retries", Skip(3)],
+  ["token", Str],
+]);
+const sessionBincode = encoder.init().encodeAs(
+  { id: 1, cache: new Map([["a", 1]]), retries: 0, token: "t" },
+  Session
+);
+assert(
+  sessionBincode.length === 4 + 8 + 1,
+  "skipped fields should not be written"
+);
+const session = decoder.load(sessionBincode.buffer).decodeAs(Session);
+assert(
+  session.cache.size === 0 && session.retries === 3 && session.token === "t",
+  "skipped fields should decode as defaults"
+);
+
+// `#[serde(default)] note: Option<String>` is on the wire like any Option
+type Draft = {
+  title: string;
+  note?: string;
+  revision: number;
+};
+const Draft = Struct<Draft>([
+  ["title", Str],
+  ["note", OptionalProp(Str)],
+  ["revision", Skip(1)],
+]);
+const draftBincode = encoder.init().encodeAs({ title: "t", revision: 9 }, Draft);
+const draft = decoder.load(draftBincode.buffer).decodeAs(Draft);
+assert(
+  draftBincode.length === 8 + 1 + 1 &&
+    draft.note === undefined &&
+    !("note" in draft) &&
+    draft.revision === 1,
+  "Option with default should keep its tag, skipped fields decode as default"
+);
+
+// inlined fields next to a nested struct
+type Metadata = {
+  createdAt: bigint;
+  author: string;
+};
+const metadataFields: TypeEntries<Metadata> = [
+  ["createdAt", u64],
+  ["author", Str],
+];
+const Metadata = Struct<Metadata>(metadataFields);
+type Post = Metadata & {
+  title: string;
+  meta: Metadata;
+};
+const Post = Struct<Post>([
+  ...metadataFields,
+  ["title", Str],
+  ["meta", Metadata],
+]);
+const post: Post = {
+  createdAt: 1n,
+  author: "alice",
+  title: "hello",
+  meta: { createdAt: 2n, author: "bob" },
+};
+const postBincode = encoder.init().encodeAs(post, Post);
+const flatBincode = encoder
+  .init()
+  .encodeAs(
+    [1n, "alice", "hello", 2n, "bob"],
+    Tuple<[bigint, string, string, bigint, string]>([u64, Str, Str, u64, Str])
+  );
+assert(
+  postBincode.join() === flatBincode.join(),
+  "inlined fields should be written in place"
+);
+assert(
+  decoder.load(postBincode.buffer).decodeAs(Post).meta.author === "bob",
+  "inlined fields should round trip"
+);
+
+// reproducible schema hash
+assert(
+  schemaHash("a") === 0xaf63dc4c8601ec8cn,
+  "schema hash should be FNV-1a 64"
+);
+const PERSON_SCHEMA_HASH = schemaHash("Person{name:String,age:u32}");
+const hashedBincode = encoder
+  .init()
+  .encodeAs({ name: "bob", age: 1 }, SchemaChecked(PERSON_SCHEMA_HASH, Person));
+assert(
+  decoder.load(hashedBincode.buffer).decodeAs(u64) === PERSON_SCHEMA_HASH,
+  "schema hash should lead the payload"
+);
+
+// class instances
+class Vector {
+  x: number;
+  y: number;
+  constructor(fields: { x: number; y: number }) {
+    this.x = fields.x;
+    this.y = fields.y;
+  }
+  length(): number {
+    return Math.hypot(this.x, this.y);
+  }
+}
+class Segment {
+  from: Vector;
+  to: Vector;
+  constructor(fields: { from: Vector; to: Vector }) {
+    this.from = fields.from;
+    this.to = fields.to;
+  }
+  delta(): Vector {
+    return new Vector({
+      x: this.to.x - this.from.x,
+      y: this.to.y - this.from.y,
+    });
+  }
+}
+const VectorType = Class(Vector, [
+  ["x", f64],
+  ["y", f64],
+]);
+const SegmentType = Class(Segment, [
+  ["from", VectorType],
+  ["to", VectorType],
+]);
+const segmentBincode = encoder
+  .init()
+  .encodeAs(
+    new Segment({
+      from: new Vector({ x: 1, y: 1 }),
+      to: new Vector({ x: 4, y: 5 }),
+    }),
+    SegmentType
+  );
+const segment = decoder.load(segmentBincode.buffer).decodeAs(SegmentType);
+assert(
+  segment instanceof Segment &&
+    segment.from instanceof Vector &&
+    segment.delta().length() === 5,
+  "nested classes should decode into instances"
+);
+
+// deep cons list
+const U32List = ConsList(u32);
+let deepList: ConsList<number> = { variant: 0, data: undefined };
+for (let idx = 0; idx < 200000; idx += 1) {
+  deepList = { variant: 1, data: [idx, deepList] };
+}
+const deepBincode = encoder.init().encodeAs(deepList, U32List);
+let deepNode = decoder.load(deepBincode.buffer).decodeAs(U32List);
+let deepLength = 0;
+while (deepNode.variant === 1) {
+  assert(
+    deepNode.data[0] === 199999 - deepLength,
+    "list items should keep order"
+  );
+  deepLength += 1;
+  deepNode = deepNode.data[1];
+}
+assert(deepLength === 200000, "200k deep list should decode");
+
+// tuple variant with many fields
+namespace Packet {
+  export enum _ {
+    Header,
+    Body,
+  }
+  export type Header = [number, number, number, Array<number>];
+  export type $ = Variant<_.Header, Header> | Variant<_.Body, Uint8Array>;
+  export const Type: Type<$> = Enum<$>({
+    [_.Header]: Tuple<Header>([u8, u16, u32, Arr(u8, 4)]),
+    [_.Body]: Bytes,
+  });
+}
+const header = enumData<Packet.$>(Packet._.Header, [
+  1,
+  0x0203,
+  0x04050607,
+  [8, 9, 10, 11],
+]);
+const headerBincode = encoder.init().encodeAs(header, Packet.Type);
+assert(
+  headerBincode.join() === "0,0,0,0,1,3,2,7,6,5,4,8,9,10,11",
+  "tuple variant fields should be written back-to-back"
+);
+assert(
+  JSON.stringify(decoder.load(headerBincode.buffer).decodeAs(Packet.Type)) ===
+    JSON.stringify(header),
+  "tuple variant should round trip"
+);
+
+// boxed slices
+const BoxedSlices = Tuple<[Array<number>, string, string]>([
+  Box(Vec(u32)),
+  Box(Str),
+  Arc(Str),
+]);
+const boxedBincode = encoder
+  .init()
+  .encodeAs([[1, 2, 3], "boxed", "shared"], BoxedSlices);
+assert(
+  boxedBincode.join() ===
+    encoder
+      .init()
+      .encodeAs(
+        [[1, 2, 3], "boxed", "shared"],
+        Tuple<[Array<number>, string, string]>([Vec(u32), Str, Str])
+      )
+      .join(),
+  "Box<[u32]>, Box<str> and Arc<str> should be encoded as Vec<u32> and String"
+);
+
+// adjacently tagged `enum Command { Ping, Say(String) }`, written by serde as a struct of tag and content
+namespace Command {
+  export enum _ {
+    Ping,
+    Say,
+  }
+  export type $ = Variant<_.Ping> | Variant<_.Say, string>;
+  export const Type = Enum<$>({
+    [_.Ping]: empty,
+    [_.Say]: Str,
+  });
+}
+// the tag is a unit variant index, the content a String
+const sayBincode = new Uint8Array([1, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 104, 105]);
+assert(
+  JSON.stringify(decoder.load(sayBincode.buffer).decodeAs(Command.Type)) ===
+    JSON.stringify(enumData<Command.$>(Command._.Say, "hi")),
+  "adjacently tagged variants should decode with Enum"
+);
+assert(
+  decoder.load(new Uint8Array([0, 0, 0, 0]).buffer).decodeAs(Command.Type)
+    .variant === Command._.Ping,
+  "adjacently tagged unit variants have no content"
+);
+
+// nested enums `enum Outer { A(Inner), B }` and `enum Inner { X(u8), Y }`
+type Inner = Variant<0, number> | Variant<1>;
+type Outer = Variant<0, Inner> | Variant<1>;
+const Inner = Enum<Inner>([u8, empty]);
+const Outer = Enum<Outer>([Inner, empty]);
+const outers: Array<Outer> = [
+  { variant: 0, data: { variant: 0, data: 9 } },
+  { variant: 0, data: { variant: 1, data: undefined } },
+  { variant: 1, data: undefined },
+];
+const outersBincode = encoder.init().encodeAs(outers, Vec(Outer));
+assert(
+  outersBincode.join() ===
+    "3,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,9,0,0,0,0,1,0,0,0,1,0,0,0" &&
+    JSON.stringify(decoder.load(outersBincode).decodeAs(Vec(Outer))) ===
+      JSON.stringify(outers),
+  "nested enums should read the outer then the inner variant"
+);
+
+// internally tagged
+type Shape = { kind: "Circle"; radius: number } | { kind: "Empty" };
+const Shape = InternallyTagged<Shape>("kind", {
+  Circle: Struct([["radius", u32]]),
+  Empty: empty,
+});
+const circleBincode = encoder
+  .init()
+  .encodeAs({ kind: "Circle", radius: 3 }, Shape);
+assert(
+  circleBincode.join() === "6,0,0,0,0,0,0,0,67,105,114,99,108,101,3,0,0,0",
+  "internally tagged variants should start with the variant name"
+);
+assert(
+  JSON.stringify(decoder.load(circleBincode.buffer).decodeAs(Shape)) ===
+    '{"kind":"Circle","radius":3}',
+  "internally tagged variants should decode into tagged objects"
+);
+const emptyShape = decoder
+  .load(encoder.init().encodeAs({ kind: "Empty" }, Shape).buffer)
+  .decodeAs(Shape);
+assert(
+  JSON.stringify(emptyShape) === '{"kind":"Empty"}',
+  "internally tagged unit variants should only have the tag"
+);
+
+// untagged
+type SettingsV1 = { version: number; name: string };
+type SettingsV2 = { version: number; name: string; retries: number };
+const SettingsV1 = Struct<SettingsV1>([
+  ["version", u8],
+  ["name", Str],
+]);
+const SettingsV2 = Struct<SettingsV2>([
+  ["version", u8],
+  ["name", Str],
+  ["retries", u32],
+]);
+const AnySettings = Untagged<SettingsV1 | SettingsV2>(
+  (decoder) => (u8.decode(decoder) === 1 ? SettingsV1 : SettingsV2),
+  (settings) => (settings.version === 1 ? SettingsV1 : SettingsV2)
+);
+const anySettings: Array<SettingsV1 | SettingsV2> = [
+  { version: 2, name: "b", retries: 3 },
+  { version: 1, name: "a" },
+];
+const anySettingsBincode = encoder
+  .init()
+  .encodeAs(anySettings, Vec(AnySettings));
+assert(
+  anySettingsBincode.join() ===
+    "2,0,0,0,0,0,0,0,2,1,0,0,0,0,0,0,0,98,3,0,0,0,1,1,0,0,0,0,0,0,0,97",
+  "untagged variants should be written without a tag"
+);
+assert(
+  JSON.stringify(
+    decoder.load(anySettingsBincode.buffer).decodeAs(Vec(AnySettings))
+  ) === JSON.stringify(anySettings),
+  "untagged variants should be decoded by the discriminator"
+);
+
+// inline struct
+type Point3 = { x: number; y: number; z: number };
+const point3Fields: TypeEntries<Point3> = [
+  ["x", u32],
+  ["y", u32],
+  ["z", u32],
+];
+const points = [
+  { x: 1, y: 2, z: 3 },
+  { x: 4, y: 5, z: 6 },
+];
+const pointsBincode = encoder
+  .init()
+  .encodeAs(points, Vec(InlineStruct<Point3>(point3Fields)));
+assert(
+  pointsBincode.join() ===
+    encoder.init().encodeAs(points, Vec(Struct<Point3>(point3Fields))).join() &&
+    JSON.stringify(
+      decoder
+        .load(pointsBincode.buffer)
+        .decodeAs(Vec(InlineStruct<Point3>(point3Fields)))
+    ) === JSON.stringify(points),
+  "InlineStruct should share the Struct layout"
+);
+
+// nested options and vecs
+const MaybeNames = Vec(Nullable(Str));
+const maybeNamesBincode = encoder
+  .init()
+  .encodeAs(["a", null, "bc"], MaybeNames);
+assert(
+  maybeNamesBincode.join() ===
+    "3,0,0,0,0,0,0,0,1,1,0,0,0,0,0,0,0,97,0,1,2,0,0,0,0,0,0,0,98,99" &&
+    JSON.stringify(decoder.load(maybeNamesBincode.buffer).decodeAs(MaybeNames)) ===
+      '["a",null,"bc"]',
+  "Vec<Option<String>> should read a tag byte per element"
+);
+const MaybePeople = Nullable(Vec(Person));
+for (const people of [null, [], [{ name: "alice", age: 30 }]]) {
+  const maybePeopleBincode = encoder.init().encodeAs(people, MaybePeople);
+  assert(
+    JSON.stringify(
+      decoder.load(maybePeopleBincode.buffer).decodeAs(MaybePeople)
+    ) === JSON.stringify(people),
+    "Option<Vec<Person>> should round trip"
+  );
+}
+const MaybeBoxed = Vec(Option(Box(Person)));
+const maybeBoxed = [Some({ name: "bob", age: 1 }), None()];
+const maybeBoxedBincode = encoder.init().encodeAs(maybeBoxed, MaybeBoxed);
+assert(
+  maybeBoxedBincode.join() ===
+    encoder.init().encodeAs(maybeBoxed, Vec(Option(Person))).join() &&
+    JSON.stringify(decoder.load(maybeBoxedBincode.buffer).decodeAs(MaybeBoxed)) ===
+      JSON.stringify(maybeBoxed),
+  "Vec<Option<Box<Person>>> should be encoded as Vec<Option<Person>>"
+);
+
+// deeply nested collections
+const Cube = Vec(Vec(Vec(u8)));
+const cube = [[[1, 2], []], [], [[3], [4, 5, 6]]];
+const cubeBincode = encoder.init().encodeAs(cube, Cube);
+assert(
+  cubeBincode.length === 8 + (8 + 8 + 2 + 8) + 8 + (8 + 8 + 1 + 8 + 3) &&
+    JSON.stringify(decoder.load(cubeBincode.buffer).decodeAs(Cube)) ===
+      JSON.stringify(cube),
+  "3-level nested vec should have a length at each level"
+);
+const Teams = HashMap(Str, Vec(Person));
+const teams = new Map([
+  ["red", [{ name: "alice", age: 30 }]],
+  ["blue", []],
+]);
+const decodedTeams = decoder
+  .load(encoder.init().encodeAs(teams, Teams).buffer)
+  .decodeAs(Teams);
+assert(
+  JSON.stringify([...decodedTeams]) === JSON.stringify([...teams]),
+  "map of vecs should round trip"
+);
+
+// type aliases shared by structs
+type Score = number;
+const Score: Type<Score> = f64;
+type Tags = Array<string>;
+const Tags: Type<Tags> = Vec(Str);
+type Article = { tags: Tags; score: Score };
+type Author = { name: string; tags: Tags; best: Score };
+const Article = Struct<Article>([
+  ["tags", Tags],
+  ["score", Score],
+]);
+const Author = Struct<Author>([
+  ["name", Str],
+  ["tags", Tags],
+  ["best", Score],
+]);
+const articleBincode = encoder.init().encodeAs({ tags: ["a"], score: 0.5 }, Article);
+const authorBincode = encoder
+  .init()
+  .encodeAs({ name: "n", tags: ["a"], best: 0.5 }, Author);
+assert(
+  decoder.load(articleBincode.buffer).decodeAs(Article).tags[0] === "a" &&
+    decoder.load(authorBincode.buffer).decodeAs(Author).best === 0.5 &&
+    authorBincode.length === 8 + 1 + articleBincode.length,
+  "aliases should decode with the aliased type"
+);
+
+// fixed array of enums, `[Message; 4]`
+const Inbox = Arr<Message.$>(Message.Type, 4);
+const inbox = [
+  dataMessage,
+  dataMessage,
+  enumData<Message.$>(Message._.Data, {
+    content: "",
+    people: [],
+    scores: new Map(),
+    size: 0,
+  }),
+  dataMessage,
+];
+const inboxBincode = encoder.init().encodeAs(inbox, Inbox);
+assert(
+  inboxBincode.length === 3 * messageBincode.length + 4 + 8 + 8 + 8 + 4 &&
+    JSON.stringify(decoder.load(inboxBincode.buffer).decodeAs(Inbox)) ===
+      JSON.stringify(inbox),
+  "[Message; 4] should be four messages without a length"
+);
+
+// shared state behind locks
+const SharedState = Tuple<[number, Array<string>]>([
+  Arc(Mutex(u32)),
+  RwLock(Vec(Str)),
+]);
+const sharedBincode = encoder.init().encodeAs([5, ["a", "b"]], SharedState);
+assert(
+  sharedBincode.join() ===
+    encoder
+      .init()
+      .encodeAs([5, ["a", "b"]], Tuple<[number, Array<string>]>([u32, Vec(Str)]))
+      .join() &&
+    JSON.stringify(decoder.load(sharedBincode.buffer).decodeAs(SharedState)) ===
+      '[5,["a","b"]]',
+  "Arc<Mutex<u32>> and RwLock<Vec<String>> should be encoded as the inner value"
+);
+
+// hand-written type for a `#[serde(with = "...")]` field
+const HexColor: Type<number> = {
+  decode: (decoder: Decoder): number =>
+    parseInt(Str.decode(decoder).slice(1), 16),
+  encode: (data: number, encoder: Encoder): void =>
+    Str.encode(`#${data.toString(16).padStart(6, "0")}`, encoder),
+};
+type Theme = { name: string; color: number; size: number };
+const Theme = Struct<Theme>([
+  ["name", Str],
+  ["color", HexColor],
+  ["size", u32],
+]);
+const themeBincode = encoder
+  .init()
+  .encodeAs({ name: "dark", color: 0x00ff80, size: 12 }, Theme);
+assert(
+  themeBincode.length === 8 + 4 + 8 + 7 + 4 &&
+    JSON.stringify(decoder.load(themeBincode.buffer).decodeAs(Theme)) ===
+      JSON.stringify({ name: "dark", color: 0x00ff80, size: 12 }),
+  "hand-written field type should decode alongside normal fields"
+);
+
+// map container chosen by key type
+const ByName = AutoMap(Str, u32);
+const ById = AutoMap(u32, u32);
+const byNameBincode = encoder.init().encodeAs({ a: 1, b: 2 }, ByName);
+const byName: Record<string, number> = decoder
+  .load(byNameBincode.buffer)
+  .decodeAs(ByName);
+const byIdBincode = encoder.init().encodeAs(
+  new Map([
+    [3, 30],
+    [1, 10],
+  ]),
+  ById
+);
+const byId: Map<number, number> = decoder.load(byIdBincode.buffer).decodeAs(ById);
+assert(
+  !(byName instanceof Map) &&
+    JSON.stringify(byName) === '{"a":1,"b":2}' &&
+    byId instanceof Map &&
+    [...byId.keys()].join() === "3,1",
+  "String keys should decode as Record, u32 keys as Map in wire order"
+);
+const ByChar = AutoMap(char, u8);
+assert(
+  decoder
+    .load(encoder.init().encodeAs({ x: 1 }, ByChar).buffer)
+    .decodeAs(ByChar).x === 1,
+  "char keys should decode as Record"
+);
@@ -0,0 +1,475 @@
+import { strict as assert } from "node:assert";
+import {
+  BincodeDecodeError,
+  Config,
+  decode,
+  DecodeOptions,
+  decodeToJson,
+  Decoder,
+  Encoder,
+} from "../src";
+import { enumData, UnknownVariant, Variant } from "../src/enum-data";
+import {
+  Arr,
+  bool,
+  Bytes,
+  empty,
+  Enum,
+  f64,
+  HashMap,
+  HashSet,
+  i16,
+  i32,
+  i64,
+  LazyStr,
+  Nullable,
+  ObjectMap,
+  Origin,
+  Str,
+  StrBytes,
+  Struct,
+  Tuple,
+  u32,
+  u64,
+  Vec,
+} from "../src/rust-type";
+
+const decoder = new Decoder();
+const encoder = new Encoder();
+
+// mark and reset
+const speculative = encoder.init().encodeAs(42, u32);
+decoder.load(speculative.buffer).mark();
+let speculativeThrown = false;
+try {
+  decoder.decodeAs(u64);
+  decoder.unmark();
+} catch (e) {
+  speculativeThrown = true;
+  decoder.reset();
+}
+assert(speculativeThrown, "decoding u64 from 4 bytes should fail");
+assert(decoder.cursor === 0, "reset should rewind cursor to the mark");
+decoder.mark().mark();
+decoder.decodeAs(u32);
+decoder.reset();
+assert(decoder.cursor === 0, "nested reset should rewind to inner mark");
+assert(decoder.decodeAs(u32) === 42, "second attempt should succeed");
+decoder.reset();
+assert(decoder.cursor === 0, "outer reset should rewind to outer mark");
+
+// varint encoding
+type Opcode = Variant<1> | Variant<300, number>;
+const Opcode = Enum<Opcode>({
+  1: empty,
+  300: u32,
+});
+const varintEncoder = new Encoder(new Config().with_varint_encoding());
+const varintDecoder = new Decoder(new Config().with_varint_encoding());
+const opcodeBincode = varintEncoder
+  .init()
+  .encodeAs(enumData<Opcode>(300, 70000), Opcode);
+assert(
+  opcodeBincode.join() === "251,44,1,252,112,17,1,0",
+  "variant above 255 should be written as a varint"
+);
+const opcode = varintDecoder.load(opcodeBincode.buffer).decodeAs(Opcode);
+assert(
+  opcode.variant === 300 && opcode.data === 70000,
+  "variant above 255 should be decoded from a varint"
+);
+const signed = varintEncoder
+  .init()
+  .encodeAs([-1, 1, -64n], Tuple<[number, number, bigint]>([i32, i16, i64]));
+assert(signed.join() === "1,2,127", "signed varint should be zigzag encoded");
+
+// decode error with offset and path
+type Inner = {
+  metadata: [number, number];
+};
+type Outer = {
+  id: number;
+  inner: Inner;
+};
+const Outer = Struct<Outer>([
+  ["id", u32],
+  [
+    "inner",
+    Struct<Inner>([["metadata", Tuple<[number, number]>([u32, u32])]]),
+  ],
+]);
+const truncated = encoder
+  .init()
+  .encodeAs({ id: 1, inner: { metadata: [2, 3] } }, Outer);
+assert.throws(
+  () => decoder.load(truncated.slice(0, 10).buffer).decodeAs(Outer),
+  (e) =>
+    e instanceof BincodeDecodeError &&
+      e.code === "UnexpectedEnd" &&
+      e.offset === 8 &&
+      e.path.join(".") === "inner.metadata.1",
+  "decode error should carry offset and path"
+);
+assert(decoder.path.length === 0, "path should be unwound after failure");
+
+// unknown variant
+const unknownVariant = new Encoder().init();
+unknownVariant.writeVariant(2);
+assert.throws(
+  () => {
+    decoder
+      .load(unknownVariant.buffer.slice(0, unknownVariant.cursor).buffer)
+      .decodeAs(Enum<Variant<0> | Variant<1, number>>([empty, u32]));
+  },
+  (e) =>
+    e instanceof BincodeDecodeError &&
+      e.code === "UnknownVariant" &&
+      e.message.includes("unknown variant 2, max known variant is 1"),
+  "unknown variant should report observed and max known variant"
+);
+
+// wire versions
+// `bincode::serialize(&vec!["ab", "c"])` of bincode 1.x
+const legacyBincode = new Uint8Array([
+  2, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 97, 98, 1, 0, 0, 0, 0, 0, 0, 0,
+  99,
+]);
+const legacyDecoder = new Decoder(new Config().with_wire_version("1"));
+assert(
+  legacyDecoder.load(legacyBincode).decodeAs(Vec(Str)).join() === "ab,c",
+  "bincode 1.x Vec<String> should be decoded"
+);
+assert(
+  new Encoder(new Config().with_wire_version("2"))
+    .init()
+    .encodeAs(["ab", "c"], Vec(Str))
+    .join() === [2, 2, 97, 98, 1, 99].join(),
+  "wire version 2 should use varint lengths"
+);
+
+// decode options
+const Pair = Tuple<[number, string]>([u32, Str]);
+const pairBincode = encoder.init().encodeAs([1, "pair"], Pair);
+const padded = new Uint8Array(pairBincode.length + 2);
+padded.set(pairBincode);
+assert(
+  decode(pairBincode, Pair, { strict: true, maxBytes: 64 }).join() === "1,pair",
+  "decode should accept a value within options"
+);
+for (const [bytes, options, code] of [
+  [padded, { strict: true, maxBytes: 64 }, "TrailingBytes"],
+  [pairBincode, { strict: true, maxBytes: 8 }, "LimitExceeded"],
+] as Array<[Uint8Array, DecodeOptions, string]>) {
+  assert.throws(
+    () => decode(bytes, Pair, options),
+    (e) => e instanceof BincodeDecodeError && e.code === code,
+    `decode should fail with ${code}`
+  );
+}
+assert(
+  decode(padded, Pair, { maxBytes: 64 }).join() === "1,pair",
+  "decode should allow trailing bytes unless strict"
+);
+
+// tolerant variants
+type Status = Variant<0> | Variant<1>;
+const Status = Enum<Status>([empty, empty]);
+const tolerant = new Decoder(new Config().with_tolerant_variants());
+const newerStatus = new Encoder().init().encodeAs(
+  [{ variant: 2, data: undefined }],
+  Vec(Enum<Variant<2>>({ 2: empty }))
+);
+const statuses = tolerant.load(newerStatus).decodeAs(Vec(Status));
+assert(
+  statuses.length === 1 &&
+    statuses[0].variant === 2 &&
+    (statuses[0] as unknown as UnknownVariant).unknown,
+  "unknown unit variant should be tolerated"
+);
+type Shape = Variant<0, number> | Variant<1, [number, number]>;
+const Shape = Enum<Shape>([u32, Tuple([u32, u32])]);
+const newerShape = new Encoder().init().encodeAs(
+  [{ variant: 2, data: 7 }],
+  Vec(Enum<Variant<2, number>>({ 2: u32 }))
+);
+assert.throws(
+  () => tolerant.load(newerShape).decodeAs(Vec(Shape)),
+  (e) => e instanceof BincodeDecodeError && e.code === "UnknownVariant",
+  "unknown variant with a payload should still be an unknown variant"
+);
+const lastShape = tolerant
+  .load(newerShape.subarray(0, newerShape.length - 4))
+  .decodeAs(Vec(Shape));
+assert(
+  lastShape[0].variant === 2,
+  "unknown variant at the end of input should be tolerated"
+);
+
+// decode to json
+type Dump = {
+  id: bigint;
+  raw: Uint8Array;
+  tags: Map<string, number>;
+};
+const Dump = Struct<Dump>([
+  ["id", u64],
+  ["raw", Bytes],
+  ["tags", HashMap(Str, u32)],
+]);
+const dumpBincode = encoder.init().encodeAs(
+  {
+    id: 1n << 60n,
+    raw: new Uint8Array([0, 171, 255]),
+    tags: new Map([["a", 1]]),
+  },
+  Dump
+);
+assert(
+  JSON.stringify(JSON.parse(decodeToJson(dumpBincode, Dump))) ===
+    '{"id":"1152921504606846976","raw":"00abff","tags":[["a",1]]}',
+  "decode to json should stringify bigint, bytes and maps"
+);
+const names = encoder.init().encodeAs(["ab", "cd"], Tuple([Str, Str]));
+assert.strictEqual(
+  decodeToJson(names, Tuple([StrBytes, LazyStr])),
+  '[\n  "ab",\n  "cd"\n]',
+  "decode to json should stringify lazy strings as strings"
+);
+
+// length limit
+const oversized = new Encoder().init();
+oversized.writeLength(4_000_000_000);
+assert.throws(
+  () => {
+    decoder
+      .load(oversized.buffer.slice(0, oversized.cursor).buffer)
+      .decodeAs(Bytes);
+  },
+  (e) =>
+    e instanceof BincodeDecodeError &&
+      e.code === "UnexpectedEnd" &&
+      e.offset === 0,
+  "oversized length should be rejected before allocation"
+);
+assert.throws(
+  () => {
+    decoder
+      .load(oversized.buffer.slice(0, oversized.cursor).buffer)
+      .decodeAs(Vec(u32));
+  },
+  (e) =>
+    e instanceof BincodeDecodeError &&
+      e.code === "UnexpectedEnd" &&
+      e.offset === 8,
+  "oversized vec should fail at its first missing element"
+);
+// zero-sized elements take no bytes, any length is valid
+const units = new Array(1000).fill(undefined);
+const unitsBincode = encoder.init().encodeAs(units, Vec(empty));
+assert(
+  unitsBincode.length === 8 &&
+    decoder.load(unitsBincode).decodeAs(Vec(empty)).length === 1000,
+  "Vec<()> should round trip without payload bytes"
+);
+const limitedDecoder = new Decoder(new Config().with_limit(8));
+assert.throws(
+  () => {
+    limitedDecoder
+      .load(encoder.init().encodeAs(["0123456789"], Vec(Str)).buffer)
+      .decodeAs(Vec(Str));
+  },
+  (e) => e instanceof BincodeDecodeError && e.code === "LimitExceeded",
+  "reading past the limit should be rejected"
+);
+assert.strictEqual(
+  limitedDecoder.load(unitsBincode).decodeAs(Vec(empty)).length,
+  1000,
+  "zero-sized elements should not count against the limit"
+);
+
+// decode context
+const pool = new Map<string, string>();
+let internHits = 0;
+const internDecoder = new Decoder().withContext({
+  onString: (value) => {
+    const interned = pool.get(value);
+    if (interned !== undefined) {
+      internHits += 1;
+      return interned;
+    }
+    pool.set(value, value);
+    return value;
+  },
+});
+type Labelled = { label: string; children: Array<[string, number]> };
+const Labelled = Struct<Labelled>([
+  ["label", Str],
+  ["children", Vec(Tuple<[string, number]>([Str, u32]))],
+]);
+const labelledBincode = encoder.init().encodeAs(
+  {
+    label: "leaf",
+    children: [
+      ["leaf", 1],
+      ["leaf", 2],
+      ["root", 3],
+    ],
+  },
+  Labelled
+);
+const labelled = internDecoder.load(labelledBincode.buffer).decodeAs(Labelled);
+assert(
+  internHits === 2 && pool.size === 2 && labelled.children[2][0] === "root",
+  "onString should be called for strings in nested types"
+);
+assert(
+  decoder.load(labelledBincode.buffer).decodeAs(Labelled).label === "leaf" &&
+    internHits === 2,
+  "decoders without a context should be unchanged"
+);
+
+// views with a nonzero byte offset, like a pooled node Buffer
+const pooled = new Uint8Array(64);
+const pointBytes = encoder.init().encodeAs(["pooled", 7], Tuple<[string, number]>([Str, u32]));
+pooled.set(pointBytes, 13);
+const pointView = pooled.subarray(13, 13 + pointBytes.length);
+assert(
+  decoder.load(pointView).decodeAs(Tuple<[string, number]>([Str, u32])).join() ===
+    "pooled,7",
+  "decoding should honor byteOffset of a view"
+);
+assert.throws(
+  () => decoder.load(pooled.subarray(13, 13 + 10)).decodeAs(Str),
+  (e) =>
+    e instanceof BincodeDecodeError && e.code === "UnexpectedEnd" && e.offset === 0,
+  "decoding should honor byteLength of a view, with offsets relative to it"
+);
+const NodeBuffer = (globalThis as any).Buffer;
+if (NodeBuffer !== undefined) {
+  const nodeBuffer = NodeBuffer.concat([NodeBuffer.from([1, 2, 3]), pointBytes]);
+  assert(
+    decoder
+      .load(nodeBuffer.subarray(3))
+      .decodeAs(Tuple<[string, number]>([Str, u32]))
+      .join() === "pooled,7",
+    "decoding should read a node Buffer in place"
+  );
+}
+
+// concatenated values
+const concatenated = new Uint8Array(64);
+const idsBincode = encoder
+  .init()
+  .encodeAs([1, 2, 3], Vec(u32))
+  .subarray(8);
+concatenated.set(idsBincode, 5);
+decoder.load(concatenated.subarray(5, 5 + idsBincode.length));
+assert(
+  decoder.remaining() === 12 && decoder.hasMore(),
+  "remaining should count bytes of the view"
+);
+const ids: number[] = [];
+while (decoder.hasMore()) {
+  ids.push(decoder.decodeAs(u32));
+}
+assert(
+  ids.join() === "1,2,3" && decoder.remaining() === 0,
+  "hasMore should stop at the end of the view"
+);
+
+// decode into
+type Soldier = { id: number; tags: Array<string>; pos: [number, number] };
+const Soldiers = Vec(
+  Struct<Soldier>([
+    ["id", u32],
+    ["tags", Vec(Str)],
+    ["pos", Tuple<[number, number]>([i16, i16])],
+  ])
+);
+const soldiersTarget: Array<Soldier> = [];
+const firstSoldiers = [
+  { id: 1, tags: ["a", "b"], pos: [1, 2] as [number, number] },
+  { id: 2, tags: [], pos: [3, 4] as [number, number] },
+];
+decoder
+  .load(encoder.init().encodeAs(firstSoldiers, Soldiers))
+  .decodeInto(Soldiers, soldiersTarget);
+const firstSoldier = soldiersTarget[0];
+const firstTags = firstSoldier.tags;
+const nextSoldiers = [{ id: 5, tags: ["c"], pos: [-1, -2] as [number, number] }];
+const decodedSoldiers = decoder
+  .load(encoder.init().encodeAs(nextSoldiers, Soldiers))
+  .decodeInto(Soldiers, soldiersTarget);
+assert(
+  decodedSoldiers === soldiersTarget &&
+    soldiersTarget[0] === firstSoldier &&
+    firstSoldier.tags === firstTags &&
+    JSON.stringify(soldiersTarget) === JSON.stringify(nextSoldiers),
+  "decodeInto should reuse objects and arrays of the target"
+);
+
+// skip
+type Sample = {
+  flag: boolean;
+  ratio: number;
+  shape: Variant<0> | Variant<1, [number, string]>;
+  tags: Map<string, Array<number>>;
+  raw: Uint8Array;
+  ids: Set<bigint>;
+  extra: Record<string, string | null>;
+  fixed: Array<number>;
+};
+const Sample = Struct<Sample>([
+  ["flag", bool],
+  ["ratio", f64],
+  ["shape", Enum<Sample["shape"]>([empty, Tuple([i16, Str])])],
+  ["tags", HashMap(Str, Vec(i32))],
+  ["raw", Bytes],
+  ["ids", HashSet(i64)],
+  ["extra", ObjectMap(Nullable(Str))],
+  ["fixed", Arr(u32, 2)],
+]);
+const sample: Sample = {
+  flag: true,
+  ratio: 0.5,
+  shape: enumData<Sample["shape"]>(1, [-300, "square"]),
+  tags: new Map([["a", [1, -70000]]]),
+  raw: new Uint8Array([1, 2, 3]),
+  ids: new Set([-1n, 1n << 40n]),
+  extra: { x: null, y: "z" },
+  fixed: [7, 8],
+};
+for (const config of [new Config(), new Config().with_varint_encoding()]) {
+  const sampleBincode = new Encoder(config)
+    .init()
+    .encodeAs([sample, 42], Tuple<[Sample, number]>([Sample, u32]));
+  const sampleDecoder = new Decoder(config).load(sampleBincode);
+  sampleDecoder.decodeAs(Sample);
+  const decodedEnd = sampleDecoder.cursor;
+  sampleDecoder.load(sampleBincode).skipAs(Sample);
+  assert(
+    sampleDecoder.cursor === decodedEnd &&
+      sampleDecoder.decodeAs(u32) === 42,
+    "skip should leave the cursor where decoding would"
+  );
+}
+assert(
+  decoder.load(new Uint8Array([0, 0, 9, 0, 0, 0])).skip(2).decodeAs(u32) === 9,
+  "skip should advance by bytes"
+);
+
+// origin of decode errors
+type Account = { owner: Outer };
+const Account = Origin(
+  "crate::bank::Account",
+  Struct<Account>([["owner", Origin("crate::model::Outer", Outer)]])
+);
+assert.throws(
+  () => decoder.load(truncated.slice(0, 10).buffer).decodeAs(Account),
+  (e) =>
+    e instanceof BincodeDecodeError &&
+      e.origin === "crate::model::Outer" &&
+      e.message.endsWith("in `crate::model::Outer`") &&
+      e.path.join(".") === "owner.inner.metadata.1",
+  "decode error should carry the innermost origin"
+);
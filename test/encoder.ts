@@ -0,0 +1,170 @@
+import { strict as assert } from "node:assert";
+import {
+  Config,
+  Decoder,
+  Encoder,
+  encodedSize,
+  frame,
+  unframe,
+} from "../src";
+import {
+  HashMap,
+  HashSet,
+  i128,
+  i16,
+  i64,
+  i8,
+  OptionalProp,
+  Str,
+  Struct,
+  Type,
+  u32,
+  u64,
+  u8,
+  Vec,
+} from "../src/rust-type";
+
+const encoder = new Encoder();
+
+// strict encode
+type Point = {
+  x: number;
+  y: number;
+};
+const Point = Struct<Point>([
+  ["x", u32],
+  ["y", u32],
+]);
+const pointWithExtra = { x: 1, y: 2, z: 3 } as Point;
+encoder.config.with_strict_encode();
+assert.throws(
+  () => encoder.init().encodeAs(pointWithExtra, Point),
+  /unexpected field `z`/,
+  "strict encode should reject unknown fields"
+);
+assert.throws(
+  () => encoder.init().encodeAs({ x: 1 } as Point, Point),
+  /missing field `y`/,
+  "strict encode should reject missing fields"
+);
+type Sparse = { id: number; note?: string };
+const Sparse = Struct<Sparse>([
+  ["id", u32],
+  ["note", OptionalProp(Str)],
+]);
+assert.strictEqual(
+  encoder.init().encodeAs({ id: 1 }, Sparse).join(),
+  "1,0,0,0,0",
+  "strict encode should allow absent optional props"
+);
+encoder.config.skip_strict_encode();
+assert(
+  encoder.init().encodeAs(pointWithExtra, Point).length === 8,
+  "non-strict encode should ignore unknown fields"
+);
+
+// sorted map keys
+const Counts = HashMap(Str, u32);
+const counts = new Map([
+  ["b", 2],
+  ["😀", 3],
+  ["a", 1],
+  ["\uffff", 4],
+]);
+const reversedCounts = new Map([...counts].reverse());
+encoder.config.with_sorted_map_keys();
+const sortedCounts = encoder.init().encodeAs(counts, Counts);
+assert(
+  sortedCounts.join() === encoder.init().encodeAs(reversedCounts, Counts).join(),
+  "sorted encode should not depend on insertion order"
+);
+assert(
+  [...new Decoder().load(sortedCounts.buffer).decodeAs(Counts).keys()].join() ===
+    "a,b,\uffff,😀",
+  "keys should be sorted by code point and decoded in wire order"
+);
+assert(
+  encoder.init().encodeAs(new Set([3, 1, 2]), HashSet(u32)).join() ===
+    encoder.init().encodeAs(new Set([1, 2, 3]), HashSet(u32)).join(),
+  "sorted encode should apply to sets"
+);
+encoder.config.skip_sorted_map_keys();
+assert(
+  encoder.init().encodeAs(counts, Counts).join() !==
+    encoder.init().encodeAs(reversedCounts, Counts).join(),
+  "unsorted encode should keep insertion order"
+);
+
+// framing
+const payload = encoder.init().encodeAs({ x: 3, y: 4 }, Point);
+const framed = frame(payload);
+assert(
+  framed.length === payload.length + 4 && framed[0] === payload.length,
+  "frame should prepend a u32 length"
+);
+assert(
+  unframe(framed).join() === payload.join(),
+  "unframe should return the payload"
+);
+const bigEndianFramed = frame(payload, new Config().with_big_endian());
+assert(bigEndianFramed[3] === payload.length, "frame should follow config");
+
+// integer ranges
+const rangeCases: Array<[Type<any>, any, any, any, any]> = [
+  [u8, 0, 255, -1, 256],
+  [i8, -128, 127, -129, 128],
+  [i16, -32768, 32767, -32769, 32768],
+  [u32, 0, 0xffffffff, 1.5, 0x100000000],
+  [u64, 0n, (1n << 64n) - 1n, -1n, 1n << 64n],
+  [i64, -(1n << 63n), (1n << 63n) - 1n, 1, 1n << 63n],
+  [i128, -(1n << 127n), (1n << 127n) - 1n, -(1n << 127n) - 1n, 1n << 127n],
+];
+for (const [type, min, max, ...invalid] of rangeCases) {
+  encoder.init().encodeAs(min, type);
+  encoder.init().encodeAs(max, type);
+  for (const value of invalid) {
+    let rangeThrown = false;
+    try {
+      encoder.init().encodeAs(value, type);
+    } catch (e) {
+      rangeThrown = e instanceof RangeError;
+    }
+    assert(rangeThrown, `encoding ${value} should be out of range`);
+  }
+}
+
+// encoded size
+type TestData = {
+  id: bigint;
+  name: string;
+  scores: Array<number>;
+  labels: Map<string, number>;
+};
+const TestData = Struct<TestData>([
+  ["id", u64],
+  ["name", Str],
+  ["scores", Vec(u32)],
+  ["labels", HashMap(Str, i16)],
+]);
+const testData: TestData = {
+  id: 70000n,
+  name: "名前",
+  scores: [1, 300, 70000],
+  labels: new Map([["a", -1]]),
+};
+for (const config of [new Config(), new Config().with_varint_encoding()]) {
+  assert(
+    encodedSize(testData, TestData, config) ===
+      new Encoder(config).init().encodeAs(testData, TestData).length,
+    "encoded size should match the encoded bytes"
+  );
+}
+const Wide: Type<number> = {
+  decode: (decoder) => decoder.read(32).getUint8(0),
+  encode: (data, encoder) => encoder.write(32).setUint8(31, data),
+};
+assert.strictEqual(
+  encodedSize(7, Wide),
+  32,
+  "encoded size should count writes wider than the scratch buffer"
+);
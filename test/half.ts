@@ -0,0 +1,39 @@
+import { strict as assert } from "node:assert";
+import { Decoder, Encoder } from "../src";
+import { bf16, f16 } from "../src/half";
+import { u16 } from "../src/rust-type";
+
+const decoder = new Decoder();
+const encoder = new Encoder();
+
+const halfValues: Array<[number, number]> = [
+  [0x3c00, 1],
+  [0xc000, -2],
+  [0x7bff, 65504],
+  [0x0001, 2 ** -24],
+  [0x03ff, 1023 * 2 ** -24],
+  [0x7c00, Infinity],
+  [0xfc00, -Infinity],
+];
+for (const [bits, value] of halfValues) {
+  const bitsBincode = encoder.init().encodeAs(bits, u16);
+  assert(
+    decoder.load(bitsBincode.buffer).decodeAs(f16) === value,
+    `f16 bits ${bits.toString(16)} should decode to ${value}`
+  );
+  assert(
+    encoder.init().encodeAs(value, f16).join() === bitsBincode.join(),
+    `f16 ${value} should encode to bits ${bits.toString(16)}`
+  );
+}
+const nanBincode = encoder.init().encodeAs(0x7e00, u16);
+assert(
+  Number.isNaN(decoder.load(nanBincode.buffer).decodeAs(f16)),
+  "f16 NaN should decode"
+);
+const bf16Bincode = encoder.init().encodeAs(Math.PI, bf16);
+assert(
+  decoder.load(bf16Bincode.buffer).decodeAs(u16) === 0x4049 &&
+    decoder.load(bf16Bincode.buffer).decodeAs(bf16) === 3.140625,
+  "bf16 should round to nearest"
+);
@@ -16,6 +16,17 @@ import {
   empty,
   Vec,
 } from "../src/rust-type";
+import "./bytes";
+import "./chrono";
+import "./compound-types";
+import "./decoder";
+import "./encoder";
+import "./half";
+import "./message";
+import "./primitive-types";
+import "./registry";
+import "./stream";
+import "./uuid";
 const decoder = new Decoder();
 const encoder = new Encoder();
 decoder.config.with_little_endian().skip_fixed_array_length();
@@ -0,0 +1,47 @@
+import { strict as assert } from "node:assert";
+import { BincodeDecodeError, MessageDispatcher } from "../src";
+import { Str, Struct, u32, Vec } from "../src/rust-type";
+
+type Login = { user: string };
+type Move = { x: number; y: number };
+const dispatcher = new MessageDispatcher()
+  .add(7, Struct<Login>([["user", Str]]))
+  .add(
+    9,
+    Struct<Move>([
+      ["x", u32],
+      ["y", u32],
+    ])
+  )
+  .add(10, Vec(u32));
+
+const moveBincode = dispatcher.encodeMessage({ variant: 9, data: { x: 1, y: 2 } });
+assert(
+  moveBincode.join() === "9,0,0,0,1,0,0,0,2,0,0,0",
+  "message should start with its u32 id"
+);
+const message = dispatcher.decodeMessage(moveBincode);
+if (message.variant === 9) {
+  assert(message.data.y === 2, "message should decode with its type");
+} else {
+  assert.fail("message id should be dispatched");
+}
+const loginBincode = dispatcher.encodeMessage({
+  variant: 7,
+  data: { user: "alice" },
+});
+assert(
+  JSON.stringify(dispatcher.decodeMessage(loginBincode.subarray(0))) ===
+    '{"variant":7,"data":{"user":"alice"}}',
+  "each message id should have its own type"
+);
+assert.throws(
+  () => dispatcher.decodeMessage(new Uint8Array([8, 0, 0, 0])),
+  (e) => e instanceof BincodeDecodeError && e.code === "UnknownVariant",
+  "unknown message id should be rejected"
+);
+assert.throws(
+  () => dispatcher.add(7, u32),
+  /message id 7 is already registered/,
+  "registering an id twice should throw"
+);
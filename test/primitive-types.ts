@@ -1,3 +1,309 @@
-import { RustType } from "../src";
+import { strict as assert } from "node:assert";
+import {
+  BincodeDecodeError,
+  Config,
+  Decoder,
+  Encoder,
+  RustType,
+} from "../src";
 
 const { i8, u8, i16, u16, i32, u32, i64, u64, f32, f64, empty } = RustType;
+const { NonZeroU32, NonZeroU64 } = RustType;
+
+const decoder = new Decoder();
+const encoder = new Encoder();
+
+// NonZero
+const nonZeroBincode = encoder.init().encodeAs(7, u32);
+assert(
+  decoder.load(nonZeroBincode.buffer).decodeAs(NonZeroU32) === 7,
+  "NonZeroU32 should decode a nonzero value"
+);
+const zeroBincode = encoder.init().encodeAs(0n, u64);
+assert.throws(
+  () => decoder.load(zeroBincode.buffer).decodeAs(NonZeroU64),
+  (e) => e instanceof BincodeDecodeError && e.code === "InvalidValue",
+  "NonZeroU64 should reject zero"
+);
+
+// special floats
+const specialFloats = [
+  Infinity,
+  -Infinity,
+  NaN,
+  -0,
+  Number.MIN_VALUE,
+  2.2250738585072009e-308,
+];
+const floatsBincode = encoder
+  .init()
+  .encodeAs(specialFloats, RustType.Vec(f64));
+const decodedFloats = decoder
+  .load(floatsBincode.buffer)
+  .decodeAs(RustType.Vec(f64));
+assert(
+  decodedFloats.every((value, i) => Object.is(value, specialFloats[i])),
+  "f64 infinities, NaN, -0 and subnormals should round trip"
+);
+const f64Nan = new Uint8Array([0, 0, 0, 0, 0, 0, 0xf8, 0x7f]);
+assert(
+  encoder
+    .init()
+    .encodeAs(decoder.load(f64Nan.buffer).decodeAs(f64), f64)
+    .join() === f64Nan.join(),
+  "f64::NAN should be re-encoded with the same bits"
+);
+const f32Specials = [Infinity, -Infinity, NaN, -0, 1.401298464324817e-45];
+const f32Bincode = encoder.init().encodeAs(f32Specials, RustType.Vec(f32));
+assert(
+  decoder
+    .load(f32Bincode.buffer)
+    .decodeAs(RustType.Vec(f32))
+    .every((value, i) => Object.is(value, f32Specials[i])),
+  "f32 infinities, NaN, -0 and subnormals should round trip"
+);
+const f32Nan = new Uint8Array([0, 0, 0xc0, 0x7f]);
+assert(
+  encoder
+    .init()
+    .encodeAs(decoder.load(f32Nan.buffer).decodeAs(f32), f32)
+    .join() === f32Nan.join(),
+  "f32::NAN should be re-encoded with the same bits"
+);
+
+// numeric wrappers
+const Counters = RustType.Vec(RustType.Wrapping(u64));
+const counters = [0n, 1n, 18446744073709551615n];
+const countersBincode = encoder.init().encodeAs(counters, Counters);
+assert(
+  countersBincode.length === 8 + 3 * 8 &&
+    decoder.load(countersBincode.buffer).decodeAs(Counters).join() ===
+      counters.join(),
+  "Vec<Wrapping<u64>> should be encoded as Vec<u64>"
+);
+assert(
+  RustType.Saturating(u32) === u32,
+  "Saturating should be transparent"
+);
+
+// signed 64-bit integers
+// `bincode::serialize(&-2i64)`, two's complement little endian
+const minusTwo = new Uint8Array([254, 255, 255, 255, 255, 255, 255, 255]);
+assert.strictEqual(
+  decoder.load(minusTwo).decodeAs(i64),
+  -2n,
+  "i64 should decode as a signed integer"
+);
+assert.strictEqual(
+  encoder.init().encodeAs(-2n, i64).join(),
+  minusTwo.join(),
+  "negative i64 should encode as two's complement"
+);
+assert.strictEqual(
+  decoder.load(minusTwo).decodeAs(u64),
+  18446744073709551614n,
+  "u64 should decode the same bytes as unsigned"
+);
+
+// timestamps
+const Seconds = RustType.Timestamp(i64, "seconds");
+const Millis = RustType.Timestamp(u32, "millis");
+const secondsBincode = encoder.init().encodeAs(1672560000n, i64);
+assert(
+  decoder.load(secondsBincode.buffer).decodeAs(Seconds).getTime() ===
+    1672560000000,
+  "seconds timestamp should decode as Date"
+);
+const millisBincode = encoder.init().encodeAs(new Date(123456), Millis);
+assert(
+  decoder.load(millisBincode.buffer).decodeAs(u32) === 123456 &&
+    decoder.load(millisBincode.buffer).decodeAs(Millis).getTime() === 123456,
+  "millis timestamp should round trip"
+);
+assert(
+  decoder
+    .load(encoder.init().encodeAs(new Date(-86400000), Seconds).buffer)
+    .decodeAs(Seconds)
+    .getTime() === -86400000,
+  "negative seconds timestamp should round trip"
+);
+const WrappedMillis = RustType.Timestamp(
+  RustType.Origin("crate::Event::at", i64),
+  "millis",
+  true
+);
+const I128Millis = RustType.Timestamp(RustType.i128, "millis");
+for (const Stamp of [WrappedMillis, I128Millis]) {
+  assert.strictEqual(
+    decoder
+      .load(encoder.init().encodeAs(new Date(-123456), Stamp))
+      .decodeAs(Stamp)
+      .getTime(),
+    -123456,
+    "bigint timestamps should round trip through wrappers and i128"
+  );
+}
+
+// char
+const chars = ["a", "é", "字", "😀"];
+const charsBincode = encoder.init().encodeAs(chars, RustType.Vec(RustType.char));
+assert(
+  charsBincode.length === 8 + 1 + 2 + 3 + 4 &&
+    decoder.load(charsBincode.buffer).decodeAs(RustType.Vec(RustType.char)).join() ===
+      chars.join(),
+  "char should be written as its utf-8 bytes"
+);
+assert.throws(
+  () => encoder.init().encodeAs("\ud800", RustType.char),
+  RangeError,
+  "char should not encode a lone surrogate"
+);
+assert.throws(
+  () =>
+    decoder
+      .load(new Uint8Array([0xed, 0xa0, 0x80]).buffer)
+      .decodeAs(RustType.char),
+  (e) => e instanceof BincodeDecodeError && e.code === "InvalidValue",
+  "char should reject surrogates"
+);
+
+// char and bool keyed maps
+const CharCounts = RustType.HashMap(RustType.char, u8);
+const charCountsBincode = encoder.init().encodeAs(
+  new Map([
+    ["x", 1],
+    ["字", 2],
+  ]),
+  CharCounts
+);
+assert(
+  decoder.load(charCountsBincode.buffer).decodeAs(CharCounts).get("字") === 2,
+  "char keyed map should decode into Map"
+);
+const Flags = RustType.HashMap(RustType.bool, RustType.Str);
+const flagsBincode = encoder.init().encodeAs(
+  new Map([
+    [true, "on"],
+    [false, "off"],
+  ]),
+  Flags
+);
+assert(
+  decoder.load(flagsBincode.buffer).decodeAs(Flags).get(false) === "off",
+  "bool keyed map should decode into Map"
+);
+const badFlag = flagsBincode.slice();
+badFlag[8] = 2;
+assert.throws(
+  () => decoder.load(badFlag.buffer).decodeAs(Flags),
+  (e) => e instanceof BincodeDecodeError && e.code === "InvalidValue",
+  "bool byte other than 0 or 1 should throw"
+);
+
+// strict bool
+const boolBytes = new Uint8Array([0, 1, 2]);
+const StrictBools = RustType.Arr(RustType.bool, 2);
+assert(
+  decoder.load(boolBytes.buffer).decodeAs(StrictBools).join() === "false,true",
+  "strict bool should decode 0 and 1"
+);
+let strictBoolThrown = false;
+try {
+  decoder.load(boolBytes.slice(2).buffer).decodeAs(RustType.bool);
+} catch (e) {
+  strictBoolThrown =
+    e instanceof BincodeDecodeError && e.code === "InvalidValue" && e.offset === 0;
+}
+assert(strictBoolThrown, "strict bool should reject byte 2");
+const laxDecoder = new Decoder(new Config().skip_strict_bool());
+assert(
+  laxDecoder.load(boolBytes.slice(2).buffer).decodeAs(RustType.bool) === true,
+  "lax bool should decode nonzero bytes as true"
+);
+assert.throws(
+  () => decoder.load(boolBytes.slice(2)).decodeAs(RustType.Nullable(u8)),
+  (e) =>
+    e instanceof BincodeDecodeError && e.code === "InvalidValue" && e.offset === 0,
+  "strict bool should reject option tag 2"
+);
+assert.strictEqual(
+  laxDecoder.load(new Uint8Array([2, 7])).decodeAs(RustType.Nullable(u8)),
+  7,
+  "lax bool should decode a nonzero option tag as Some"
+);
+
+// 128-bit integers
+const maxU128 = (1n << 128n) - 1n;
+const u128Bincode = encoder.init().encodeAs(maxU128 - 1n, RustType.u128);
+assert(
+  u128Bincode.length === 16 &&
+    u128Bincode[0] === 0xfe &&
+    decoder.load(u128Bincode.buffer).decodeAs(RustType.u128) === maxU128 - 1n,
+  "u128 should be written as 16 bytes"
+);
+const i128Bincode = encoder.init().encodeAs(-2n, RustType.i128);
+assert(
+  i128Bincode.join() === [0xfe, ...new Array(15).fill(0xff)].join() &&
+    decoder.load(i128Bincode.buffer).decodeAs(RustType.i128) === -2n,
+  "i128 should be written as two's complement"
+);
+const varintConfig = new Config().with_varint_encoding();
+const varintI128 = new Encoder(varintConfig)
+  .init()
+  .encodeAs(-(1n << 100n), RustType.i128);
+assert(
+  varintI128[0] === 254 &&
+    new Decoder(varintConfig).load(varintI128.buffer).decodeAs(RustType.i128) ===
+      -(1n << 100n),
+  "varint i128 should be zigzag encoded with tag 254"
+);
+
+// enum written by bincode 2's native `Encode` with the standard config:
+// enum Event { Started, Progress(u32), Done { code: i128 } }
+const Event = RustType.Enum({
+  0: empty,
+  1: u32,
+  2: RustType.Struct([["code", RustType.i128]]),
+});
+const nativeDecoder = new Decoder(varintConfig);
+assert(
+  JSON.stringify(
+    nativeDecoder.load(new Uint8Array([1, 251, 0x10, 0x27]).buffer).decodeAs(Event)
+  ) === '{"variant":1,"data":10000}',
+  "native encoded tuple variant should decode"
+);
+assert(
+  nativeDecoder
+    .load(new Uint8Array([2, 3]).buffer)
+    .decodeAs(Event)
+    .data.code === -2n,
+  "native encoded struct variant should decode"
+);
+
+// unit
+type Ack = { id: number; done: null; ping: null | undefined };
+const Ack = RustType.Struct<Ack>([
+  ["id", u32],
+  ["done", RustType.Unit()],
+  ["ping", RustType.Optional(RustType.Unit())],
+]);
+const someAck = encoder.init().encodeAs({ id: 1, done: null, ping: null }, Ack);
+const noneAck = encoder
+  .init()
+  .encodeAs({ id: 1, done: null, ping: undefined }, Ack);
+assert(
+  someAck.join() === "1,0,0,0,1" && noneAck.join() === "1,0,0,0,0",
+  "() should take no byte, Option<()> only its tag"
+);
+assert(
+  decoder.load(someAck.buffer).decodeAs(Ack).ping === null &&
+    decoder.load(noneAck.buffer).decodeAs(Ack).ping === undefined &&
+    decoder.load(noneAck.buffer).decodeAs(Ack).done === null,
+  "() should decode as null, Option<()> as null or undefined"
+);
+assert(
+  decoder
+    .load(new Uint8Array([1]).buffer)
+    .decodeAs(RustType.Option(RustType.Unit("unit"))).data === "unit",
+  "unit token should be configurable"
+);
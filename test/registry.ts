@@ -0,0 +1,169 @@
+import { strict as assert } from "node:assert";
+import { Decoder, Encoder, TypeRegistry } from "../src";
+import { Variant } from "../src/enum-data";
+import {
+  None,
+  Option,
+  Some,
+  Str,
+  Struct,
+  Type,
+  u32,
+  Vec,
+} from "../src/rust-type";
+
+const decoder = new Decoder();
+const encoder = new Encoder();
+const registry = new TypeRegistry();
+
+// Department refers to Employee before Employee is registered, and back
+type Department = {
+  name: string;
+  employees: Array<Employee>;
+};
+type Employee = {
+  name: string;
+  manages: Variant<0> | Variant<1, Department>;
+};
+const Department = registry.add(
+  "Department",
+  Struct<Department>([
+    ["name", Str],
+    ["employees", Vec(registry.ref<Employee>("Employee"))],
+  ])
+);
+registry.add(
+  "Employee",
+  Struct<Employee>([
+    ["name", Str],
+    ["manages", Option(registry.ref<Department>("Department"))],
+  ])
+);
+const department: Department = {
+  name: "root",
+  employees: [
+    {
+      name: "alice",
+      manages: Some({
+        name: "sub",
+        employees: [{ name: "bob", manages: None() }],
+      }),
+    },
+  ],
+};
+const departmentBincode = encoder.init().encodeAs(department, Department);
+assert(
+  JSON.stringify(
+    decoder.load(departmentBincode.buffer).decodeAs(Department)
+  ) === JSON.stringify(department),
+  "registry refs should resolve regardless of order"
+);
+assert.throws(
+  () => registry.add("Employee", Str),
+  /type `Employee` is already registered/,
+  "registering a different type twice should throw"
+);
+
+// override a third-party type with a hand-written one
+type Rgb = { r: number; g: number; b: number };
+const Rgb: Type<Rgb> = {
+  decode: (decoder: Decoder): Rgb => {
+    const [r, g, b] = decoder.readBytes(3);
+    return { r, g, b };
+  },
+  encode: (data: Rgb, encoder: Encoder): void => {
+    encoder.writeBytes(new Uint8Array([data.r, data.g, data.b]));
+  },
+};
+registry.add("Rgb", Str);
+registry.override("Rgb", Rgb);
+const Palette = Vec(registry.ref<Rgb>("Rgb"));
+const paletteBincode = encoder.init().encodeAs([{ r: 1, g: 2, b: 3 }], Palette);
+assert(
+  paletteBincode.length === 8 + 3 &&
+    decoder.load(paletteBincode.buffer).decodeAs(Palette)[0].b === 3,
+  "overridden type should be used by refs"
+);
+
+// two modules both define `Config`, namespaces keep them apart
+type AuthConfig = { token: string };
+type Settings = { auth: AuthConfig; retries: Array<number> };
+const auth = registry.namespace("auth");
+const app = registry.namespace("app");
+auth.add("Config", Struct<AuthConfig>([["token", Str]]));
+const Settings = app.add(
+  "Settings",
+  Struct<Settings>([
+    ["auth", app.ref<AuthConfig>("::auth::Config")],
+    ["retries", Vec(app.ref<number>("Config"))],
+  ])
+);
+app.add("Config", u32);
+const settings: Settings = { auth: { token: "secret" }, retries: [1, 2] };
+const settingsBincode = encoder.init().encodeAs(settings, Settings);
+assert(
+  JSON.stringify(decoder.load(settingsBincode.buffer).decodeAs(Settings)) ===
+    JSON.stringify(settings),
+  "refs should resolve within and across namespaces"
+);
+assert(
+  registry.has("auth::Config") &&
+    registry.has("app::Config") &&
+    !registry.has("Config"),
+  "namespaced names should be qualified"
+);
+
+// referred but unregistered types
+assert(registry.check() === registry, "all refs should be registered");
+const incomplete = new TypeRegistry();
+incomplete.add(
+  "Order",
+  Struct<{ items: Array<any> }>([
+    ["items", Vec(incomplete.ref("Item", "Order"))],
+  ])
+);
+incomplete.namespace("billing").ref("Invoice");
+incomplete.ref("Item");
+assert.throws(
+  () => incomplete.check(),
+  (e) =>
+    e instanceof Error &&
+      e.message ===
+        "referred types are not registered: " +
+          "`Item` referenced by `Order`, the root namespace; " +
+          "`billing::Invoice` referenced by `billing`",
+  "check should list the unregistered refs and their referrers"
+);
+
+// decoders by name
+type Plugin = { name: string; version: number };
+const plugins = new TypeRegistry<{ Plugin: Plugin; Id: number }>();
+plugins.add(
+  "Plugin",
+  Struct<Plugin>([
+    ["name", Str],
+    ["version", u32],
+  ])
+);
+plugins.add("Id", u32);
+const Envelope = Struct<{ name: string; payload: Plugin }>([
+  ["name", Str],
+  ["payload", plugins.get<Plugin>("Plugin")],
+]);
+const envelope = encoder
+  .init()
+  .encodeAs({ name: "Plugin", payload: { name: "lint", version: 3 } }, Envelope);
+decoder.load(envelope.buffer);
+const pluginName = decoder.decodeAs(Str);
+const plugin: Plugin = plugins.getDecoder("Plugin")(decoder);
+assert(
+  pluginName === "Plugin" &&
+    plugin.name === "lint" &&
+    plugin.version === 3 &&
+    plugins.decoders[pluginName] !== undefined,
+  "decoder should be looked up by name"
+);
+assert(
+  Object.keys(plugins.decoders).join() === "Plugin,Id",
+  "decoders should cover all registered types"
+);
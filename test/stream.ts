@@ -0,0 +1,257 @@
+import { strict as assert } from "node:assert";
+import {
+  BincodeDecodeError,
+  Decoder,
+  decodeVecAsync,
+  decodeVecStream,
+  Encoder,
+  encodeVecStream,
+} from "../src";
+import { Str, Struct, Type, u32, Vec } from "../src/rust-type";
+
+const encoder = new Encoder();
+
+// stream a top-level vec, with elements spanning chunk boundaries
+type Message = {
+  id: number;
+  text: string;
+};
+const Message = Struct<Message>([
+  ["id", u32],
+  ["text", Str],
+]);
+const messages: Message[] = [
+  { id: 1, text: "hello" },
+  { id: 2, text: "早上好" },
+  { id: 3, text: "" },
+];
+const messagesBincode = encoder.init().encodeAs(messages, Vec(Message));
+const stream = new ReadableStream<Uint8Array>({
+  start(controller) {
+    for (let idx = 0; idx < messagesBincode.length; idx += 5) {
+      controller.enqueue(messagesBincode.slice(idx, idx + 5));
+    }
+    controller.close();
+  },
+});
+(async () => {
+  const decoded: Message[] = [];
+  for await (const message of decodeVecStream(stream, Message)) {
+    decoded.push(message);
+  }
+  assert(
+    JSON.stringify(decoded) === JSON.stringify(messages),
+    "streamed vec should match"
+  );
+  assert(!stream.locked, "stream should be released once decoded");
+})();
+(async () => {
+  // a vec of one `String` whose length runs past the end of stream
+  const truncatedStream = new ReadableStream<Uint8Array>({
+    start(controller) {
+      controller.enqueue(messagesBincode.slice(0, 14));
+      controller.close();
+    },
+  });
+  const controller = new AbortController();
+  await assert.rejects(async () => {
+    for await (const _ of decodeVecStream(truncatedStream, Message, {
+      signal: controller.signal,
+    })) {
+    }
+  }, BincodeDecodeError);
+  assert(
+    !truncatedStream.locked,
+    "stream should be released when decoding fails"
+  );
+})();
+
+// a value spanning many chunks is retried once its declared length is buffered
+(async () => {
+  const long = "x".repeat(10000);
+  const longBincode = encoder.init().encodeAs([long], Vec(Str));
+  let attempts = 0;
+  const CountedStr: Type<string> = {
+    decode: (decoder) => {
+      attempts += 1;
+      return Str.decode(decoder);
+    },
+    encode: Str.encode,
+  };
+  const chunked = new ReadableStream<Uint8Array>({
+    start(controller) {
+      for (let idx = 0; idx < longBincode.length; idx += 100) {
+        controller.enqueue(longBincode.slice(idx, idx + 100));
+      }
+      controller.close();
+    },
+  });
+  const decoded: string[] = [];
+  for await (const text of decodeVecStream(chunked, CountedStr)) {
+    decoded.push(text);
+  }
+  assert(
+    decoded.length === 1 && decoded[0] === long && attempts === 2,
+    "a long value should not be decoded again on every chunk"
+  );
+})();
+
+// a value finished by a small chunk is decoded without waiting for more
+(async () => {
+  const pairBincode = encoder.init().encodeAs([1, 2], Vec(u32));
+  const chunks = [pairBincode.slice(0, 13), pairBincode.slice(13)];
+  const live = new ReadableStream<Uint8Array>({
+    pull(streamController) {
+      const chunk = chunks.shift();
+      if (chunk) {
+        streamController.enqueue(chunk);
+        return;
+      }
+      // the socket stays open
+      return new Promise(() => {});
+    },
+  });
+  const collect = async () => {
+    const decoded: number[] = [];
+    for await (const value of decodeVecStream(live, u32)) {
+      decoded.push(value);
+    }
+    return decoded;
+  };
+  let timer: ReturnType<typeof setTimeout> | undefined;
+  const timeout = new Promise<string>((resolve) => {
+    timer = setTimeout(() => resolve("timeout"), 1000);
+  });
+  const decoded = await Promise.race([collect(), timeout]);
+  clearTimeout(timer);
+  assert.deepStrictEqual(
+    decoded,
+    [1, 2],
+    "an element should be decoded once its last bytes arrive"
+  );
+})();
+
+// abort a streamed vec midway
+(async () => {
+  const controller = new AbortController();
+  let canceled = false;
+  let chunkCount = 0;
+  const endless = new ReadableStream<Uint8Array>({
+    pull(streamController) {
+      // the whole vec but its last element, then nothing
+      if (chunkCount === 0) {
+        streamController.enqueue(
+          messagesBincode.slice(0, messagesBincode.length - 4)
+        );
+      }
+      chunkCount += 1;
+      return new Promise(() => {});
+    },
+    cancel() {
+      canceled = true;
+    },
+  });
+  const decoded: Message[] = [];
+  let abortError: unknown;
+  try {
+    for await (const message of decodeVecStream(endless, Message, {
+      signal: controller.signal,
+    })) {
+      decoded.push(message);
+      if (decoded.length === 2) {
+        setTimeout(() => controller.abort(new Error("canceled by user")), 0);
+      }
+    }
+  } catch (e) {
+    abortError = e;
+  }
+  assert(
+    decoded.length === 2 &&
+      abortError instanceof Error &&
+      abortError.message === "canceled by user",
+    "aborted stream decode should reject with the abort reason"
+  );
+  await new Promise((resolve) => setTimeout(resolve, 0));
+  assert(
+    canceled && !endless.locked,
+    "aborted stream should be canceled and its reader released"
+  );
+})();
+
+// async decode with cooperative yielding
+const manyMessages = Array.from({ length: 2500 }, (_, idx) => ({
+  id: idx,
+  text: `message ${idx}`,
+}));
+const manyBincode = encoder.init().encodeAs(manyMessages, Vec(Message));
+(async () => {
+  let ticks = 0;
+  const ticker = setInterval(() => (ticks += 1), 0);
+  const decoded = await decodeVecAsync(manyBincode.buffer, Message, {
+    yieldEvery: 100,
+  });
+  clearInterval(ticker);
+  const expected = new Decoder()
+    .load(manyBincode.buffer)
+    .decodeAs(Vec(Message));
+  assert(
+    JSON.stringify(decoded) === JSON.stringify(expected),
+    "async decode should match sync decode"
+  );
+  assert(ticks > 0, "async decode should yield to other tasks");
+})();
+const hugePrefix = encoder.init();
+hugePrefix.writeLength(2 ** 40);
+(async () => {
+  await assert.rejects(
+    decodeVecAsync(hugePrefix.encodeAs(1, u32), Message),
+    (e) => e instanceof BincodeDecodeError && e.code === "UnexpectedEnd",
+    "async decode should not preallocate a huge declared length"
+  );
+})();
+
+// stream encode with backpressure
+function* generateMessages(count: number): Generator<Message> {
+  for (let idx = 0; idx < count; idx += 1) {
+    yield { id: idx, text: `streamed ${idx}` };
+  }
+}
+(async () => {
+  const chunks: Uint8Array[] = [];
+  let writing = 0;
+  let maxWriting = 0;
+  const sink = new WritableStream<Uint8Array>(
+    {
+      async write(chunk) {
+        writing += 1;
+        maxWriting = Math.max(maxWriting, writing);
+        await new Promise((resolve) => setTimeout(resolve, 0));
+        chunks.push(chunk);
+        writing -= 1;
+      },
+    },
+    { highWaterMark: 1 }
+  );
+  await encodeVecStream(sink, generateMessages(100000), Message, {
+    vecLength: 100000,
+    chunkSize: 1 << 14,
+  });
+  const total = chunks.reduce((sum, chunk) => sum + chunk.length, 0);
+  const joined = new Uint8Array(total);
+  chunks.reduce((offset, chunk) => (joined.set(chunk, offset), offset + chunk.length), 0);
+  const decoded = new Decoder().load(joined).decodeAs(Vec(Message));
+  assert(
+    chunks.length > 1 &&
+      maxWriting === 1 &&
+      decoded.length === 100000 &&
+      decoded[99999].text === "streamed 99999",
+    "streamed encode should write bounded chunks decodable as a Vec"
+  );
+  await assert.rejects(
+    encodeVecStream(new WritableStream(), generateMessages(2), Message, {
+      vecLength: 3,
+    }),
+    /expect 3 elements, found 2/,
+    "element count should match the length"
+  );
+})();
@@ -0,0 +1,25 @@
+import { strict as assert } from "node:assert";
+import { Decoder, Encoder } from "../src";
+import { Uuid, UuidCompact } from "../src/uuid";
+
+const decoder = new Decoder();
+const encoder = new Encoder();
+
+const uuid = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+const uuidBincode = encoder.init().encodeAs(uuid, Uuid);
+assert(
+  uuidBincode.length === 8 + 16 &&
+    uuidBincode[0] === 16 &&
+    uuidBincode[8] === 0x67,
+  "Uuid should be written as length prefixed bytes"
+);
+assert(
+  decoder.load(uuidBincode.buffer).decodeAs(Uuid) === uuid,
+  "Uuid should round trip"
+);
+const compactBincode = encoder.init().encodeAs(uuid, UuidCompact);
+assert(
+  compactBincode.length === 16 &&
+    decoder.load(compactBincode.buffer).decodeAs(UuidCompact) === uuid,
+  "compact Uuid should round trip"
+);